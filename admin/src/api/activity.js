@@ -20,4 +20,119 @@ export function updateActivityConfig(data) {
     method: 'post',
     data
   })
-}
\ No newline at end of file
+}
+
+/**
+ * 获取全部活动
+ */
+export function getActivityList() {
+  return request({
+    url: '/activities',
+    method: 'get'
+  })
+}
+
+/**
+ * 新建或修改活动
+ * @param {Object} data { id, name, start_time, end_time }
+ */
+export function saveActivity(data) {
+  return request({
+    url: '/activities',
+    method: 'post',
+    data
+  })
+}
+
+/**
+ * 设为当前活动
+ * @param {Number} id
+ */
+export function activateActivity(id) {
+  return request({
+    url: `/activity/${id}/activate`,
+    method: 'post'
+  })
+}
+
+/**
+ * 删除活动及其灯谜、答题记录
+ * @param {Number} id
+ */
+export function deleteActivity(id) {
+  return request({
+    url: `/activity/${id}`,
+    method: 'delete'
+  })
+}
+
+/**
+ * 控制活动：暂停、恢复、立即开始、立即结束
+ * @param {Number} id
+ * @param {String} action pause | resume | open | close
+ */
+export function controlActivity(id, action) {
+  return request({
+    url: `/activity/${id}/${action}`,
+    method: 'post'
+  })
+}
+
+/**
+ * 延长活动结束时间
+ * @param {Number} id
+ * @param {Number} minutes 延长的分钟数
+ */
+export function extendActivity(id, minutes) {
+  return request({
+    url: `/activity/${id}/extend`,
+    method: 'post',
+    data: { minutes }
+  })
+}
+
+/**
+ * 获取活动的轮次
+ * @param {Number} activityId 为空时为当前活动
+ */
+export function getRoundList(activityId) {
+  return request({
+    url: '/rounds',
+    method: 'get',
+    params: { activity_id: activityId || undefined }
+  })
+}
+
+/**
+ * 新建或修改轮次
+ * @param {Object} data { id, activity_id, name, release_time }
+ */
+export function saveRound(data) {
+  return request({
+    url: '/rounds',
+    method: 'post',
+    data
+  })
+}
+
+/**
+ * 立即开放轮次
+ * @param {Number} id
+ */
+export function openRound(id) {
+  return request({
+    url: `/round/${id}/open`,
+    method: 'post'
+  })
+}
+
+/**
+ * 删除轮次，其中的灯谜改为不分轮次
+ * @param {Number} id
+ */
+export function deleteRound(id) {
+  return request({
+    url: `/round/${id}`,
+    method: 'delete'
+  })
+}
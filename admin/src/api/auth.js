@@ -0,0 +1,23 @@
+import request from './request'
+
+/**
+ * 管理员登录
+ * @param {Object} data { username, password }
+ */
+export function login(data) {
+  return request({
+    url: '/login',
+    method: 'post',
+    data
+  })
+}
+
+/**
+ * 获取当前登录的管理员信息
+ */
+export function getCurrentAdmin() {
+  return request({
+    url: '/me',
+    method: 'get'
+  })
+}
@@ -0,0 +1,53 @@
+import request from './request'
+
+// 服务端地址：与接口地址相同，去掉 /pro-api 前缀
+function serverOrigin() {
+  const base = request.defaults.baseURL || '/pro-api'
+  return base.replace(/\/pro-api\/?$/, '') || window.location.origin
+}
+
+let clientLoader = null
+
+// 使用服务端自带的 socket.io 客户端脚本，与大屏页面一致
+function loadClient() {
+  if (window.io) {
+    return Promise.resolve(window.io)
+  }
+  if (!clientLoader) {
+    clientLoader = new Promise((resolve, reject) => {
+      const script = document.createElement('script')
+      script.src = `${serverOrigin()}/frontend/js/socket.io.min.js`
+      script.onload = () => resolve(window.io)
+      script.onerror = () => {
+        clientLoader = null
+        reject(new Error('socket.io 客户端加载失败'))
+      }
+      document.head.appendChild(script)
+    })
+  }
+  return clientLoader
+}
+
+/**
+ * 连接管理后台实时命名空间 /admin
+ * 事件：guess_attempt 作答流、presence 在线人数；可发送 broadcast_banner
+ */
+export async function connectAdminSocket() {
+  const io = await loadClient()
+  return io(`${serverOrigin()}/admin`, {
+    auth: { token: localStorage.getItem('admin_token') || undefined }
+  })
+}
+
+/**
+ * 向玩家手机发送横幅
+ * @param {Object} socket connectAdminSocket 返回的连接
+ * @param {Object} data { message, activity_id, duration_secs }
+ */
+export function broadcastBanner(socket, data) {
+  return new Promise((resolve) => {
+    socket.timeout(5000).emit('broadcast_banner', data, (err, res) => {
+      resolve(err ? { code: 500, message: '发送超时' } : res)
+    })
+  })
+}
@@ -13,12 +13,26 @@ export function getRecordList(params) {
 }
 
 /**
- * 获取抢答排行榜
+ * 获取全部作答记录（包括答错的）
+ * @param {Object} params { page, pageSize, keyword, riddle_id, correct }
  */
-export function getLeaderboard() {
+export function getAttemptList(params) {
+  return request({
+    url: '/records',
+    method: 'get',
+    params
+  })
+}
+
+/**
+ * 获取积分排行榜
+ * @param {Object} params { page, pageSize, keyword, activity_id }
+ */
+export function getLeaderboard(params) {
   return request({
     url: '/stats/leaderboard',
-    method: 'get'
+    method: 'get',
+    params
   })
 }
 
@@ -15,11 +15,11 @@ const service = axios.create({
 // Request interceptor
 service.interceptors.request.use(
   config => {
-    // You can add token to headers here
-    // const token = localStorage.getItem('token')
-    // if (token) {
-    //   config.headers['Authorization'] = `Bearer ${token}`
-    // }
+    // 携带管理员登录后获得的 token；桌面端本机访问无需登录
+    const token = localStorage.getItem('admin_token')
+    if (token) {
+      config.headers['Authorization'] = `Bearer ${token}`
+    }
     return config
   },
   error => {
@@ -48,6 +48,15 @@ service.interceptors.response.use(
   },
   error => {
     console.log('err' + error)
+    // 未登录或登录已过期：清除 token 并跳转到登录页
+    if (error.response && error.response.status === 401) {
+      localStorage.removeItem('admin_token')
+      window.location.hash = '#/login'
+    }
+    const data = error.response && error.response.data
+    if (data && data.message) {
+      error.message = data.message
+    }
     ElMessage({
       message: error.message,
       type: 'error',
@@ -12,9 +12,21 @@ export function getRiddleList(params) {
   })
 }
 
+/**
+ * 获取已有的分类、标签与难度
+ * @param {Object} params { activity_id }
+ */
+export function getRiddleFacets(params) {
+  return request({
+    url: '/riddles/facets',
+    method: 'get',
+    params
+  })
+}
+
 /**
  * 新增或修改灯谜
- * @param {Object} data { id, question, answer, options, remark, reset_status }
+ * @param {Object} data { id, question, answer, options, remark, category, tags, difficulty, reset_status }
  */
 export function saveRiddle(data) {
   return request({
@@ -43,8 +55,84 @@ export function importRiddles(formData) {
     url: '/riddles/import', // 对应你 Flask 写的路由
     method: 'post',
     data: formData,
+    timeout: 60000,
+    headers: {
+      'Content-Type': 'multipart/form-data'
+    }
+  });
+}
+/**
+ * 上传灯谜的图片或音频
+ * @param {FormData} formData { file }
+ */
+export function uploadMedia(formData) {
+  return request({
+    url: '/media',
+    method: 'post',
+    data: formData,
+    timeout: 60000,
     headers: {
       'Content-Type': 'multipart/form-data'
     }
   });
-}
\ No newline at end of file
+}
+
+/**
+ * 读取表格表头，获取建议的列映射
+ * @param {FormData} formData { file }
+ */
+export function suggestImportMapping(formData) {
+  return request({
+    url: '/riddles/import/suggest',
+    method: 'post',
+    data: formData,
+    timeout: 60000,
+    headers: {
+      'Content-Type': 'multipart/form-data'
+    }
+  });
+}
+
+/**
+ * 获取保存的列映射
+ */
+export function getImportMappings() {
+  return request({
+    url: '/riddles/mappings',
+    method: 'get'
+  })
+}
+
+/**
+ * 按名称保存列映射
+ * @param {Object} data { name, mapping }
+ */
+export function saveImportMapping(data) {
+  return request({
+    url: '/riddles/mappings',
+    method: 'post',
+    data
+  })
+}
+
+/**
+ * 删除列映射
+ * @param {number|string} id
+ */
+export function deleteImportMapping(id) {
+  return request({
+    url: `/riddles/mapping/${id}`,
+    method: 'delete'
+  })
+}
+
+/**
+ * 下载按当前列定义生成的导入模板 (xlsx)
+ */
+export function downloadImportTemplate() {
+  return request({
+    url: '/riddles/template',
+    method: 'get',
+    responseType: 'blob'
+  })
+}
@@ -3,6 +3,8 @@ import RiddleManage from '../views/RiddleManage.vue';
 import ActivityManage from '../views/ActivityManage.vue';
 import RecordManage from '../views/RecordManage.vue';
 import UserManage from '../views/UserManage.vue';
+import LiveMonitor from '../views/LiveMonitor.vue';
+import Login from '../views/Login.vue';
 
 const routes = [
   {
@@ -32,6 +34,18 @@ const routes = [
     name: 'UserManage',
     component: UserManage,
     meta: { title: '用户管理', icon: 'user' }
+  },
+  {
+    path: '/live',
+    name: 'LiveMonitor',
+    component: LiveMonitor,
+    meta: { title: '实时监控', icon: 'live' }
+  },
+  {
+    path: '/login',
+    name: 'Login',
+    component: Login,
+    meta: { title: '管理员登录' }
   }
 ];
 
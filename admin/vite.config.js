@@ -16,6 +16,15 @@ export default defineConfig({
       '/pro-api': {
         target: 'http://127.0.0.1:9000',
         changeOrigin: true,
+      },
+      '/socket.io': {
+        target: 'http://127.0.0.1:9000',
+        changeOrigin: true,
+        ws: true,
+      },
+      '/frontend': {
+        target: 'http://127.0.0.1:9000',
+        changeOrigin: true,
       }
     }
   }
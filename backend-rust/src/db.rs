@@ -1,2 +0,0 @@
-// db.rs can be used for migration or specific db helpers
-// For now, it's just a placeholder as we're initializing in main.rs
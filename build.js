@@ -18,25 +18,6 @@ function run(command, args, cwd) {
   }
 }
 
-function copyRecursiveSync(src, dest) {
-  const exists = fs.existsSync(src);
-  const stats = exists && fs.statSync(src);
-  const isDirectory = exists && stats.isDirectory();
-  if (isDirectory) {
-    if (!fs.existsSync(dest)) {
-      fs.mkdirSync(dest, { recursive: true });
-    }
-    fs.readdirSync(src).forEach((childItemName) => {
-      copyRecursiveSync(
-        path.join(src, childItemName),
-        path.join(dest, childItemName)
-      );
-    });
-  } else {
-    fs.copyFileSync(src, dest);
-  }
-}
-
 const rootDir = __dirname;
 const adminDir = path.join(rootDir, "admin");
 const tauriDir = path.join(rootDir, "src-tauri");
@@ -58,7 +39,8 @@ if (isStandalone) {
   const binaryName = isWindows ? "backend-rust.exe" : "backend-rust";
   const targetBinaryName = isWindows ? "lantern-riddle-standalone.exe" : "lantern-riddle-standalone";
 
-  const releaseDir = path.join(backendRustDir, "target", "release");
+  // backend-rust 属于根目录的 Cargo 工作区，产物位于根目录 target 下
+  const releaseDir = path.join(rootDir, "target", "release");
   const binaryPath = path.join(releaseDir, binaryName);
 
   if (fs.existsSync(binaryPath)) {
@@ -75,27 +57,14 @@ if (isStandalone) {
   console.log("Standalone Build Success!");
   console.log("======================================================");
 } else {
-  console.log("[1/4] Building Vue Admin Frontend...");
+  console.log("[1/3] Building Vue Admin Frontend...");
   run("bun", ["install"], adminDir);
   run("bun", ["run", "build", "--", "--outDir", "../template/admin", "--emptyOutDir"], adminDir);
 
-  console.log("[2/4] Preparing src-tauri...");
-  const tauriSrcDir = path.join(tauriDir, "src");
-  const tauriHandlersDir = path.join(tauriSrcDir, "handlers");
-
-  if (!fs.existsSync(tauriHandlersDir)) {
-    fs.mkdirSync(tauriHandlersDir, { recursive: true });
-  }
-
-  fs.copyFileSync(path.join(backendRustDir, "src", "models.rs"), path.join(tauriSrcDir, "models.rs"));
-  fs.copyFileSync(path.join(backendRustDir, "src", "utils.rs"), path.join(tauriSrcDir, "utils.rs"));
-  fs.copyFileSync(path.join(backendRustDir, "src", "db.rs"), path.join(tauriSrcDir, "db.rs"));
-  copyRecursiveSync(path.join(backendRustDir, "src", "handlers"), tauriHandlersDir);
-
-  console.log("[3/4] Building Tauri Desktop App...");
+  console.log("[2/3] Building Tauri Desktop App...");
   run("bun", ["tauri", "build"], tauriDir);
 
-  console.log("[4/4] Copying final binary...");
+  console.log("[3/3] Copying final binary...");
   const distDesktopDir = path.join(rootDir, "dist-desktop");
   if (!fs.existsSync(distDesktopDir)) {
     fs.mkdirSync(distDesktopDir, { recursive: true });
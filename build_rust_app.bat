@@ -21,7 +21,7 @@ if %errorlevel% neq 0 (
 
 echo [3/3] Copying final binary and database...
 cd /d "%ROOT_DIR%"
-copy "backend-rust\target\release\backend-rust.exe" "dist\lantern-riddle-standalone.exe" /Y
+copy "target\release\backend-rust.exe" "dist\lantern-riddle-standalone.exe" /Y
 if exist "lantern.db" (
     copy "lantern.db" "dist\lantern.db" /Y
 )
@@ -0,0 +1,144 @@
+use chrono::{Duration, NaiveDateTime, Timelike};
+use sqlx::SqlitePool;
+use crate::models::{Activity, ActivityState};
+
+/// 新建活动时每道灯谜默认的最多作答次数
+pub const DEFAULT_MAX_ATTEMPTS: i64 = 3;
+
+/// settings 表中记录当前活动 ID 的键
+const ACTIVE_ACTIVITY_KEY: &str = "active_activity_id";
+
+pub async fn find(pool: &SqlitePool, id: i64) -> Option<Activity> {
+    sqlx::query_as("SELECT * FROM activities WHERE id = ?")
+        .bind(id)
+        .fetch_optional(pool)
+        .await
+        .unwrap_or(None)
+}
+
+/// 当前活动：管理后台选中的活动；未选择或已被删除时取最新创建的活动
+pub async fn active(pool: &SqlitePool) -> Option<Activity> {
+    let selected: Option<(String,)> = sqlx::query_as("SELECT value FROM settings WHERE key = ?")
+        .bind(ACTIVE_ACTIVITY_KEY)
+        .fetch_optional(pool)
+        .await
+        .unwrap_or(None);
+    if let Some(id) = selected.and_then(|(v,)| v.parse::<i64>().ok()) {
+        if let Some(act) = find(pool, id).await {
+            return Some(act);
+        }
+    }
+
+    sqlx::query_as("SELECT * FROM activities ORDER BY id DESC LIMIT 1")
+        .fetch_optional(pool)
+        .await
+        .unwrap_or(None)
+}
+
+pub async fn active_id(pool: &SqlitePool) -> Option<i64> {
+    active(pool).await.map(|a| a.id)
+}
+
+pub async fn set_active(pool: &SqlitePool, id: i64) -> Result<(), sqlx::Error> {
+    sqlx::query("INSERT OR REPLACE INTO settings (key, value) VALUES (?, ?)")
+        .bind(ACTIVE_ACTIVITY_KEY)
+        .bind(id.to_string())
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+/// 一次最多延长的分钟数
+pub const MAX_EXTEND_MINUTES: i64 = 24 * 60;
+
+/// 活动在 `now` 时的状态，暂停只在活动时间内生效
+pub fn state_at(act: &Activity, now: NaiveDateTime) -> ActivityState {
+    if now < act.start_time {
+        ActivityState::Scheduled
+    } else if now > act.end_time {
+        ActivityState::Ended
+    } else if act.paused_at.is_some() {
+        ActivityState::Paused
+    } else {
+        ActivityState::Running
+    }
+}
+
+/// 主持人对活动的手动控制
+#[derive(Debug, Clone, Copy)]
+pub enum Control {
+    /// 暂停作答
+    Pause,
+    /// 恢复作答
+    Resume,
+    /// 结束时间延后若干分钟，已结束的活动从现在起重新开放
+    Extend(i64),
+    /// 立即开始
+    Open,
+    /// 立即结束
+    Close,
+}
+
+/// 计算控制后的活动时间与暂停状态，不允许的操作返回提示信息
+pub fn apply(act: &Activity, control: Control, now: NaiveDateTime) -> Result<Activity, &'static str> {
+    let current = state_at(act, now);
+    // 保存的时间精确到秒
+    let now = now.with_nanosecond(0).unwrap_or(now);
+    let mut next = act.clone();
+    match (control, current) {
+        (Control::Pause, ActivityState::Running) => next.paused_at = Some(now),
+        (Control::Pause, ActivityState::Paused) => return Err("活动已经暂停"),
+        (Control::Pause, _) => return Err("只有进行中的活动可以暂停"),
+        (Control::Resume, ActivityState::Paused) => next.paused_at = None,
+        (Control::Resume, _) => return Err("活动没有暂停"),
+        (Control::Extend(minutes), _) => {
+            if !(1..=MAX_EXTEND_MINUTES).contains(&minutes) {
+                return Err("延长时间需在 1 到 1440 分钟之间");
+            }
+            next.end_time = act.end_time.max(now) + Duration::minutes(minutes);
+        }
+        (Control::Open, ActivityState::Scheduled) => {
+            next.start_time = now;
+            next.paused_at = None;
+        }
+        (Control::Open, ActivityState::Paused) => next.paused_at = None,
+        (Control::Open, ActivityState::Running) => return Err("活动已在进行中"),
+        (Control::Open, ActivityState::Ended) => return Err("活动已结束，请使用延长时间重新开放"),
+        (Control::Close, ActivityState::Ended) => return Err("活动已经结束"),
+        (Control::Close, _) => {
+            next.start_time = act.start_time.min(now);
+            next.end_time = now;
+            next.paused_at = None;
+        }
+    }
+    Ok(next)
+}
+
+/// 执行控制并保存，返回更新后的活动
+pub async fn control(pool: &SqlitePool, act: &Activity, control: Control, now: NaiveDateTime) -> Result<Activity, String> {
+    let next = apply(act, control, now).map_err(str::to_string)?;
+    sqlx::query("UPDATE activities SET start_time = ?, end_time = ?, paused_at = ? WHERE id = ?")
+        .bind(next.start_time)
+        .bind(next.end_time)
+        .bind(next.paused_at)
+        .bind(next.id)
+        .execute(pool)
+        .await
+        .map_err(|e| format!("保存失败: {}", e))?;
+    Ok(next)
+}
+
+pub async fn all(pool: &SqlitePool) -> Vec<Activity> {
+    sqlx::query_as("SELECT * FROM activities ORDER BY id DESC")
+        .fetch_all(pool)
+        .await
+        .unwrap_or_default()
+}
+
+/// 按 URL 中的活动 ID 查找活动，未指定时使用当前活动
+pub async fn resolve(pool: &SqlitePool, id: Option<i64>) -> Option<Activity> {
+    match id {
+        Some(id) => find(pool, id).await,
+        None => active(pool).await,
+    }
+}
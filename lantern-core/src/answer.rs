@@ -0,0 +1,119 @@
+use pinyin::ToPinyinMulti;
+use unicode_normalization::UnicodeNormalization;
+use crate::models::MatchMode;
+
+/// 答案归一化：NFKC (全角字母数字转半角)、转小写，并去掉所有空白与标点
+///
+/// `"北京。"`、`"北 京"`、`"ＡＢＣ"` 分别归一化为 `"北京"`、`"北京"`、`"abc"`
+pub fn normalize(text: &str) -> String {
+    text.nfkc()
+        .flat_map(char::to_lowercase)
+        .filter(|c| !c.is_whitespace() && !is_punctuation(*c))
+        .collect()
+}
+
+fn is_punctuation(c: char) -> bool {
+    c.is_ascii_punctuation()
+        || matches!(c,
+            '¡' | '§' | '«' | '¶' | '·' | '»' | '¿'
+            | '\u{2010}'..='\u{2027}'   // 连接号、引号、省略号等
+            | '\u{2030}'..='\u{205E}'
+            | '\u{3001}'..='\u{3003}'   // 、。〃
+            | '\u{3008}'..='\u{3020}'   // 各类书名号、括号
+            | '\u{3030}' | '\u{303D}'
+            | '\u{30FB}'                // 中点 ・
+            | '\u{FE10}'..='\u{FE1F}'   // 竖排标点
+            | '\u{FE30}'..='\u{FE6F}'
+        )
+}
+
+/// 解析灯谜的备选答案 (`aliases_json`)
+pub fn parse_aliases(aliases_json: &str) -> Vec<String> {
+    serde_json::from_str(aliases_json).unwrap_or_default()
+}
+
+/// 整理后台提交的备选答案：去掉空白项以及与正确答案、彼此之间归一化后重复的项
+pub fn clean_aliases(answer: &str, aliases: Vec<String>) -> Vec<String> {
+    let mut seen = vec![normalize(answer)];
+    let mut result = Vec::new();
+    for alias in aliases {
+        let alias = alias.trim().to_string();
+        let key = normalize(&alias);
+        if key.is_empty() || seen.contains(&key) {
+            continue;
+        }
+        seen.push(key);
+        result.push(alias);
+    }
+    result
+}
+
+/// 玩家答案与正确答案或任一备选答案归一化后相同即为答对，`mode` 决定是否另外接受繁体字与拼音
+pub fn is_correct(input: &str, answer: &str, aliases: &[String], mode: MatchMode) -> bool {
+    let input = normalize(input);
+    if input.is_empty() {
+        return false;
+    }
+    let simplified_input = match mode {
+        MatchMode::Normal => None,
+        MatchMode::Traditional | MatchMode::Pinyin => Some(fast2s::convert(&input)),
+    };
+
+    std::iter::once(answer)
+        .chain(aliases.iter().map(String::as_str))
+        .map(normalize)
+        .filter(|accepted| !accepted.is_empty())
+        .any(|accepted| {
+            accepted == input
+                || simplified_input.as_ref().is_some_and(|s| *s == fast2s::convert(&accepted))
+                || (mode == MatchMode::Pinyin && matches_pinyin(&input, &accepted))
+        })
+}
+
+/// 玩家输入的拼音 (不区分声调，可带 1-5 的声调数字，`ü` 可写作 `v`) 是否为答案的某种读音
+///
+/// 多音字的每个读音都接受，如 `chongqing` 与 `zhongqing` 都能匹配 “重庆”
+fn matches_pinyin(input: &str, accepted: &str) -> bool {
+    if !input.chars().all(|c| c.is_ascii_alphanumeric() || c == 'ü') {
+        return false;
+    }
+    let input: String = input
+        .chars()
+        .filter(|c| !c.is_ascii_digit())
+        .map(|c| if c == 'ü' { 'v' } else { c })
+        .collect();
+    if input.is_empty() {
+        return false;
+    }
+
+    // 答案中每个字可能的读音，非汉字按原样匹配
+    let readings: Vec<Vec<String>> = accepted
+        .chars()
+        .map(|c| match c.to_pinyin_multi() {
+            Some(multi) => multi.into_iter().map(|p| p.plain().replace('ü', "v")).collect(),
+            None => vec![c.to_string()],
+        })
+        .collect();
+
+    // positions: 已经匹配到的输入位置集合，逐字推进
+    let input = input.as_str();
+    let mut positions = vec![0usize];
+    for options in &readings {
+        let mut next: Vec<usize> = positions
+            .iter()
+            .flat_map(|&pos| {
+                options
+                    .iter()
+                    .filter(move |r| input[pos..].starts_with(r.as_str()))
+                    .map(move |r| pos + r.len())
+            })
+            .collect();
+        next.sort_unstable();
+        next.dedup();
+        if next.is_empty() {
+            return false;
+        }
+        positions = next;
+    }
+    positions.contains(&input.len())
+}
@@ -0,0 +1,257 @@
+use argon2::{
+    password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, SaltString},
+    Argon2,
+};
+use axum::{
+    extract::{ConnectInfo, Request, State},
+    http::{header, request::Parts, Extensions, HeaderMap, Method, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Json,
+};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use hmac::{Hmac, Mac};
+use rand::RngCore;
+use serde::Serialize;
+use serde_json::json;
+use sha2::Sha256;
+use sqlx::SqlitePool;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::Arc;
+use axum_extra::extract::cookie::{Cookie, CookieJar};
+use crate::{AppState, models::{Admin, AdminRole, User}, utils::now_utc};
+
+/// 管理员会话有效期 (12 小时)
+pub const ADMIN_SESSION_TTL_SECS: i64 = 12 * 60 * 60;
+
+/// 玩家会话有效期 (31 天)
+pub const PLAYER_SESSION_TTL_DAYS: i64 = 31;
+
+/// 保存玩家签名会话的 cookie 名称
+pub const PLAYER_SESSION_COOKIE: &str = "session";
+
+/// 用于签发会话的 HMAC 密钥，首次启动时生成并保存在 settings 表中
+#[derive(Clone)]
+pub struct SessionKey(Vec<u8>);
+
+impl SessionKey {
+    pub async fn load_or_create(pool: &SqlitePool) -> Result<Self, sqlx::Error> {
+        let existing: Option<(String,)> = sqlx::query_as("SELECT value FROM settings WHERE key = 'session_secret'")
+            .fetch_optional(pool)
+            .await?;
+        if let Some(secret) = existing.and_then(|(v,)| URL_SAFE_NO_PAD.decode(v).ok()) {
+            return Ok(Self(secret));
+        }
+
+        let mut secret = vec![0u8; 32];
+        rand::rngs::OsRng.fill_bytes(&mut secret);
+        sqlx::query("INSERT OR REPLACE INTO settings (key, value) VALUES ('session_secret', ?)")
+            .bind(URL_SAFE_NO_PAD.encode(&secret))
+            .execute(pool)
+            .await?;
+        Ok(Self(secret))
+    }
+
+    fn mac(&self, payload: &[u8]) -> Hmac<Sha256> {
+        let mut mac = Hmac::<Sha256>::new_from_slice(&self.0).expect("HMAC accepts keys of any length");
+        mac.update(payload);
+        mac
+    }
+
+    /// 签名：`base64(payload).base64(hmac)`
+    pub fn sign(&self, payload: &str) -> String {
+        let sig = self.mac(payload.as_bytes()).finalize().into_bytes();
+        format!("{}.{}", URL_SAFE_NO_PAD.encode(payload), URL_SAFE_NO_PAD.encode(sig))
+    }
+
+    /// 校验签名，成功时返回原始 payload
+    pub fn verify(&self, token: &str) -> Option<String> {
+        let (payload_b64, sig_b64) = token.split_once('.')?;
+        let payload = URL_SAFE_NO_PAD.decode(payload_b64).ok()?;
+        let sig = URL_SAFE_NO_PAD.decode(sig_b64).ok()?;
+        self.mac(&payload).verify_slice(&sig).ok()?;
+        String::from_utf8(payload).ok()
+    }
+}
+
+pub fn hash_password(password: &str) -> String {
+    let salt = SaltString::generate(&mut rand::rngs::OsRng);
+    Argon2::default()
+        .hash_password(password.as_bytes(), &salt)
+        .expect("Failed to hash password")
+        .to_string()
+}
+
+pub fn verify_password(password: &str, password_hash: &str) -> bool {
+    PasswordHash::new(password_hash)
+        .map(|hash| Argon2::default().verify_password(password.as_bytes(), &hash).is_ok())
+        .unwrap_or(false)
+}
+
+/// 数据库中没有任何管理员时创建默认超级管理员 `admin`
+///
+/// 未配置初始密码时随机生成一个，并打印到日志中
+pub async fn ensure_default_admin(pool: &SqlitePool, initial_password: Option<&str>) -> Result<(), sqlx::Error> {
+    let count: (i64,) = sqlx::query_as("SELECT COUNT(*) FROM admins").fetch_one(pool).await?;
+    if count.0 > 0 {
+        return Ok(());
+    }
+
+    let password = match initial_password {
+        Some(p) if !p.is_empty() => p.to_string(),
+        _ => {
+            let generated = uuid::Uuid::new_v4().simple().to_string()[..12].to_string();
+            tracing::warn!("已创建默认超级管理员 admin，初始密码: {}，请登录后尽快修改", generated);
+            generated
+        }
+    };
+
+    sqlx::query("INSERT INTO admins (username, password_hash, role, create_time) VALUES (?, ?, ?, ?)")
+        .bind("admin")
+        .bind(hash_password(&password))
+        .bind(AdminRole::SuperAdmin)
+        .bind(now_utc())
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+/// 当前请求对应的管理员，由 [`require_admin`] 写入请求扩展
+#[derive(Debug, Clone, Serialize)]
+pub struct AdminSession {
+    pub id: i64,
+    pub username: String,
+    pub role: AdminRole,
+}
+
+pub fn issue_admin_token(key: &SessionKey, admin_id: i64) -> String {
+    let expires = chrono::Utc::now().timestamp() + ADMIN_SESSION_TTL_SECS;
+    key.sign(&format!("admin:{}:{}", admin_id, expires))
+}
+
+/// 解析管理员 token，返回未过期的管理员 ID
+pub fn parse_admin_token(key: &SessionKey, token: &str) -> Option<i64> {
+    let payload = key.verify(token)?;
+    let mut parts = payload.split(':');
+    if parts.next()? != "admin" {
+        return None;
+    }
+    let admin_id: i64 = parts.next()?.parse().ok()?;
+    let expires: i64 = parts.next()?.parse().ok()?;
+    if expires < chrono::Utc::now().timestamp() {
+        return None;
+    }
+    Some(admin_id)
+}
+
+fn bearer_token(req: &Request) -> Option<String> {
+    req.headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .map(|v| v.trim().to_string())
+}
+
+fn is_loopback(extensions: &Extensions) -> bool {
+    extensions
+        .get::<ConnectInfo<SocketAddr>>()
+        .map(|ConnectInfo(addr)| addr.ip().is_loopback())
+        .unwrap_or(false)
+}
+
+/// Host 是否为本机地址，如 `localhost:9000`、`127.0.0.1:9000`、`[::1]:9000`
+fn is_local_host(host: &str) -> bool {
+    let name = match host.strip_prefix('[') {
+        Some(rest) => rest.split(']').next().unwrap_or_default(),
+        None => host.split(':').next().unwrap_or_default(),
+    };
+    name.eq_ignore_ascii_case("localhost") || name.parse::<IpAddr>().is_ok_and(|ip| ip.is_loopback())
+}
+
+/// 桌面端本机访问是否可以免登录：连接来自 loopback，Host 是本机地址 (防止 DNS 重绑定)，
+/// 且浏览器带的 Origin 与后端同源或是配置的管理后台，本机浏览器中打开的其他网页不能借此调用管理接口
+fn is_trusted_local(state: &AppState, extensions: &Extensions, headers: &HeaderMap) -> bool {
+    if !state.config.trust_local_admin || !is_loopback(extensions) {
+        return false;
+    }
+    let host = match headers.get(header::HOST).and_then(|v| v.to_str().ok()) {
+        Some(h) if is_local_host(h) => h,
+        _ => return false,
+    };
+    match headers.get(header::ORIGIN).and_then(|v| v.to_str().ok()) {
+        None => true,
+        Some(origin) => origin == format!("http://{}", host) || state.config.admin_origins.iter().any(|o| o == origin),
+    }
+}
+
+async fn current_admin(state: &AppState, token: Option<String>, is_local: bool) -> Option<AdminSession> {
+    // 桌面端：本机访问视为超级管理员
+    if is_local {
+        return Some(AdminSession { id: 0, username: "local".to_string(), role: AdminRole::SuperAdmin });
+    }
+
+    let admin_id = parse_admin_token(&state.session_key, &token?)?;
+    let admin: Admin = sqlx::query_as("SELECT * FROM admins WHERE id = ?")
+        .bind(admin_id)
+        .fetch_optional(&state.db)
+        .await
+        .unwrap_or(None)?;
+    Some(AdminSession { id: admin.id, username: admin.username, role: admin.role })
+}
+
+/// Socket.IO `/admin` 命名空间的鉴权：握手认证数据中的 token 或桌面端本机访问，任意管理员角色均可
+pub async fn socket_admin(state: &AppState, parts: &Parts, token: Option<String>) -> Option<AdminSession> {
+    current_admin(state, token, is_trusted_local(state, &parts.extensions, &parts.headers)).await
+}
+
+/// `/pro-api` 鉴权中间件：查询类请求 (GET) 需要操作员及以上，其余修改类请求需要超级管理员
+pub async fn require_admin(State(state): State<Arc<AppState>>, mut req: Request, next: Next) -> Response {
+    let required = if matches!(*req.method(), Method::GET | Method::HEAD) {
+        AdminRole::Operator
+    } else {
+        AdminRole::SuperAdmin
+    };
+
+    let is_local = is_trusted_local(&state, req.extensions(), req.headers());
+    let session = match current_admin(&state, bearer_token(&req), is_local).await {
+        Some(s) => s,
+        None => return (StatusCode::UNAUTHORIZED, Json(json!({ "code": 401, "message": "请先登录管理后台" }))).into_response(),
+    };
+    if session.role < required {
+        return (StatusCode::FORBIDDEN, Json(json!({ "code": 403, "message": "权限不足，需要超级管理员" }))).into_response();
+    }
+
+    req.extensions_mut().insert(session);
+    next.run(req).await
+}
+
+/// 为玩家签发会话 cookie，内容为签名后的 `users.token`
+pub fn player_session_cookie(key: &SessionKey, token: &str) -> Cookie<'static> {
+    Cookie::build((PLAYER_SESSION_COOKIE, key.sign(&format!("user:{}", token))))
+        .path("/")
+        .http_only(true)
+        .max_age(time::Duration::days(PLAYER_SESSION_TTL_DAYS))
+        .build()
+}
+
+/// 清除玩家会话 cookie
+pub fn expired_player_session_cookie() -> Cookie<'static> {
+    Cookie::build((PLAYER_SESSION_COOKIE, ""))
+        .path("/")
+        .max_age(time::Duration::seconds(0))
+        .build()
+}
+
+/// 根据会话 cookie 查找当前玩家
+///
+/// cookie 签名无效、token 已被注销 (退出登录或用户被删除) 或已过期时返回 `None`
+pub async fn current_user(state: &AppState, jar: &CookieJar) -> Option<User> {
+    let payload = state.session_key.verify(jar.get(PLAYER_SESSION_COOKIE)?.value())?;
+    let token = payload.strip_prefix("user:")?;
+    sqlx::query_as("SELECT * FROM users WHERE token = ? AND token_expires > ?")
+        .bind(token)
+        .bind(now_utc())
+        .fetch_optional(&state.db)
+        .await
+        .unwrap_or(None)
+}
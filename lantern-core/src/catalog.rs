@@ -0,0 +1,100 @@
+use chrono::NaiveDateTime;
+use serde::Serialize;
+use sqlx::{sqlite::SqliteRow, FromRow, SqlitePool};
+use crate::rounds;
+
+/// 分类、单个标签的最大长度 (字符)
+pub const MAX_LABEL_LEN: usize = 20;
+/// 每道灯谜最多的标签数
+pub const MAX_TAGS: usize = 10;
+
+/// 灯谜带有某个标签的条件 (`r` 为 riddles 表的别名)，需绑定标签
+pub const HAS_TAG: &str = "EXISTS (SELECT 1 FROM json_each(r.tags_json) WHERE json_each.value = ?)";
+
+/// 分类或标签及其灯谜数量
+#[derive(Debug, Serialize)]
+pub struct Facet {
+    pub name: String,
+    pub count: i64,
+}
+
+/// 活动中可供筛选的分类、标签与难度
+#[derive(Debug, Serialize)]
+pub struct Facets {
+    pub categories: Vec<Facet>,
+    pub tags: Vec<Facet>,
+    pub difficulties: Vec<i64>,
+}
+
+fn clean_label(value: &str) -> String {
+    value.trim().chars().take(MAX_LABEL_LEN).collect()
+}
+
+/// 整理分类名称：去掉首尾空白并截断，空字符串表示未分类
+pub fn clean_category(category: &str) -> String {
+    clean_label(category)
+}
+
+/// 整理标签：去掉空白项与重复项，最多保留 `MAX_TAGS` 个
+pub fn clean_tags(tags: Vec<String>) -> Vec<String> {
+    let mut result: Vec<String> = Vec::new();
+    for tag in tags {
+        let tag = clean_label(&tag);
+        if tag.is_empty() || result.contains(&tag) {
+            continue;
+        }
+        result.push(tag);
+        if result.len() == MAX_TAGS {
+            break;
+        }
+    }
+    result
+}
+
+/// 表格中同一格填写的多个标签，用逗号、顿号或 `|` 分隔
+pub fn split_tags(value: &str) -> Vec<String> {
+    value.split([',', '，', '、', '|']).map(str::to_string).collect()
+}
+
+pub fn parse_tags(tags_json: &str) -> Vec<String> {
+    serde_json::from_str(tags_json).unwrap_or_default()
+}
+
+async fn fetch<T>(pool: &SqlitePool, sql: &str, activity_id: Option<i64>, now: Option<NaiveDateTime>) -> Vec<T>
+where
+    T: for<'r> FromRow<'r, SqliteRow> + Send + Unpin,
+{
+    let mut query = sqlx::query_as::<_, T>(sql).bind(activity_id);
+    if let Some(now) = now {
+        query = query.bind(now);
+    }
+    query.fetch_all(pool).await.unwrap_or_default()
+}
+
+/// 活动中的分类、标签与难度，按灯谜数量排序；指定 `open_at` 时只统计该时刻已开放且未被猜中的灯谜
+pub async fn facets(pool: &SqlitePool, activity_id: Option<i64>, open_at: Option<NaiveDateTime>) -> Facets {
+    let scope = match open_at {
+        Some(_) => format!("r.activity_id IS ? AND r.is_solved = 0 AND {}", rounds::RELEASED),
+        None => "r.activity_id IS ?".to_string(),
+    };
+
+    let categories: Vec<(String, i64)> = fetch(pool, &format!(
+        "SELECT r.category, COUNT(*) AS n FROM riddles r WHERE {} AND r.category != '' GROUP BY r.category ORDER BY n DESC, r.category",
+        scope
+    ), activity_id, open_at).await;
+    let tags: Vec<(String, i64)> = fetch(pool, &format!(
+        "SELECT t.value, COUNT(*) AS n FROM riddles r, json_each(r.tags_json) t WHERE {} GROUP BY t.value ORDER BY n DESC, t.value",
+        scope
+    ), activity_id, open_at).await;
+    let difficulties: Vec<(i64,)> = fetch(pool, &format!(
+        "SELECT DISTINCT r.difficulty FROM riddles r WHERE {} ORDER BY r.difficulty",
+        scope
+    ), activity_id, open_at).await;
+
+    let to_facets = |rows: Vec<(String, i64)>| rows.into_iter().map(|(name, count)| Facet { name, count }).collect();
+    Facets {
+        categories: to_facets(categories),
+        tags: to_facets(tags),
+        difficulties: difficulties.into_iter().map(|(d,)| d).collect(),
+    }
+}
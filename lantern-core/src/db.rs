@@ -0,0 +1,357 @@
+use chrono::FixedOffset;
+use sqlx::sqlite::{SqliteConnectOptions, SqliteConnection, SqlitePool, SqlitePoolOptions};
+use std::path::Path;
+use std::str::FromStr;
+use std::time::Duration;
+
+/// 一次迁移中的单个步骤
+pub enum Step {
+    /// 执行一段 SQL (可包含多条语句)
+    Sql(&'static str),
+    /// 为已有表补充列；列已存在时跳过，兼容手动改过表结构的旧数据库
+    AddColumn { table: &'static str, column: &'static str, decl: &'static str },
+    /// 把按活动时区保存的本地时间换算为 UTC，时区由 `migrate` 传入 (即 `Config.timezone`)
+    ToUtc { table: &'static str, columns: &'static [&'static str] },
+}
+
+/// 版本化迁移，按 `version` 递增顺序执行，已执行的版本记录在 `schema_version` 表中
+///
+/// 只能在末尾追加新的迁移，已发布的迁移不可修改
+pub struct Migration {
+    pub version: i64,
+    pub description: &'static str,
+    pub steps: &'static [Step],
+}
+
+pub const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        description: "初始表结构",
+        steps: &[Step::Sql(r#"
+            CREATE TABLE IF NOT EXISTS users (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                username TEXT NOT NULL,
+                avatar TEXT,
+                user_code TEXT UNIQUE,
+                token TEXT UNIQUE,
+                register_time DATETIME DEFAULT CURRENT_TIMESTAMP
+            );
+            CREATE TABLE IF NOT EXISTS riddles (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                question TEXT NOT NULL,
+                remark TEXT,
+                options_json TEXT NOT NULL DEFAULT '[]',
+                answer TEXT NOT NULL,
+                add_time DATETIME DEFAULT CURRENT_TIMESTAMP,
+                is_solved BOOLEAN DEFAULT 0,
+                solver_id INTEGER,
+                FOREIGN KEY (solver_id) REFERENCES users(id)
+            );
+            CREATE TABLE IF NOT EXISTS activities (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                name TEXT NOT NULL,
+                start_time DATETIME NOT NULL,
+                end_time DATETIME NOT NULL
+            );
+            CREATE TABLE IF NOT EXISTS guess_records (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                user_id INTEGER NOT NULL,
+                riddle_id INTEGER NOT NULL,
+                solve_time DATETIME DEFAULT CURRENT_TIMESTAMP,
+                is_solved BOOLEAN DEFAULT 0,
+                FOREIGN KEY (user_id) REFERENCES users(id),
+                FOREIGN KEY (riddle_id) REFERENCES riddles(id),
+                UNIQUE(user_id, riddle_id)
+            );
+        "#)],
+    },
+    Migration {
+        version: 2,
+        description: "管理员账号与系统设置",
+        steps: &[Step::Sql(r#"
+            CREATE TABLE IF NOT EXISTS admins (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                username TEXT NOT NULL UNIQUE,
+                password_hash TEXT NOT NULL,
+                role TEXT NOT NULL DEFAULT 'operator',
+                create_time DATETIME DEFAULT CURRENT_TIMESTAMP
+            );
+            CREATE TABLE IF NOT EXISTS settings (
+                key TEXT PRIMARY KEY,
+                value TEXT NOT NULL
+            );
+        "#)],
+    },
+    Migration {
+        version: 3,
+        description: "玩家会话过期时间",
+        steps: &[Step::AddColumn { table: "users", column: "token_expires", decl: "DATETIME" }],
+    },
+    Migration {
+        version: 4,
+        description: "多活动：灯谜归属活动",
+        steps: &[
+            Step::AddColumn { table: "riddles", column: "activity_id", decl: "INTEGER REFERENCES activities(id)" },
+            // 已有灯谜归入第一个活动；旧库只有灯谜没有活动时补建一个默认活动
+            Step::Sql(r#"
+                INSERT INTO activities (name, start_time, end_time)
+                SELECT '元宵猜灯谜', datetime('now', 'localtime'), datetime('now', 'localtime', '+1 day')
+                WHERE NOT EXISTS (SELECT 1 FROM activities) AND EXISTS (SELECT 1 FROM riddles);
+                UPDATE riddles SET activity_id = (SELECT MIN(id) FROM activities) WHERE activity_id IS NULL;
+                CREATE INDEX IF NOT EXISTS idx_riddles_activity ON riddles(activity_id);
+            "#),
+        ],
+    },
+    Migration {
+        version: 5,
+        description: "灯谜备选答案",
+        steps: &[Step::AddColumn { table: "riddles", column: "aliases_json", decl: "TEXT NOT NULL DEFAULT '[]'" }],
+    },
+    Migration {
+        version: 6,
+        description: "灯谜答案匹配方式",
+        steps: &[Step::AddColumn { table: "riddles", column: "match_mode", decl: "TEXT NOT NULL DEFAULT 'normal'" }],
+    },
+    Migration {
+        version: 7,
+        description: "答题次数限制：记录每次作答",
+        steps: &[
+            Step::AddColumn { table: "activities", column: "max_attempts", decl: "INTEGER NOT NULL DEFAULT 3" },
+            Step::AddColumn { table: "activities", column: "cooldown_secs", decl: "INTEGER NOT NULL DEFAULT 0" },
+            // 去掉 UNIQUE(user_id, riddle_id)，每次作答一条记录；SQLite 不能删除约束，只能重建表
+            Step::Sql(r#"
+                CREATE TABLE guess_records_new (
+                    id INTEGER PRIMARY KEY AUTOINCREMENT,
+                    user_id INTEGER NOT NULL,
+                    riddle_id INTEGER NOT NULL,
+                    solve_time DATETIME DEFAULT CURRENT_TIMESTAMP,
+                    is_solved BOOLEAN DEFAULT 0,
+                    is_correct BOOLEAN NOT NULL DEFAULT 0,
+                    answer TEXT,
+                    FOREIGN KEY (user_id) REFERENCES users(id),
+                    FOREIGN KEY (riddle_id) REFERENCES riddles(id)
+                );
+                INSERT INTO guess_records_new (id, user_id, riddle_id, solve_time, is_solved, is_correct)
+                    SELECT id, user_id, riddle_id, solve_time, is_solved, is_solved FROM guess_records;
+                DROP TABLE guess_records;
+                ALTER TABLE guess_records_new RENAME TO guess_records;
+                CREATE INDEX IF NOT EXISTS idx_guess_records_user_riddle ON guess_records(user_id, riddle_id);
+            "#),
+        ],
+    },
+    Migration {
+        version: 8,
+        description: "答题记录保存归一化后的答案",
+        steps: &[Step::AddColumn { table: "guess_records", column: "normalized_answer", decl: "TEXT" }],
+    },
+    Migration {
+        version: 9,
+        description: "积分：灯谜分值、难度系数与抢答加分",
+        steps: &[
+            Step::AddColumn { table: "riddles", column: "points", decl: "INTEGER NOT NULL DEFAULT 10" },
+            Step::AddColumn { table: "riddles", column: "difficulty", decl: "INTEGER NOT NULL DEFAULT 1" },
+            Step::AddColumn { table: "riddles", column: "shown_at", decl: "DATETIME" },
+            Step::AddColumn { table: "activities", column: "difficulty_multipliers_json", decl: "TEXT NOT NULL DEFAULT '[]'" },
+            Step::AddColumn { table: "activities", column: "speed_bonus", decl: "INTEGER NOT NULL DEFAULT 0" },
+            Step::AddColumn { table: "activities", column: "speed_bonus_secs", decl: "INTEGER NOT NULL DEFAULT 300" },
+            Step::AddColumn { table: "guess_records", column: "points", decl: "INTEGER NOT NULL DEFAULT 0" },
+            // 已有的中奖记录按灯谜分值计分
+            Step::Sql("UPDATE guess_records SET points = COALESCE((SELECT points FROM riddles WHERE riddles.id = guess_records.riddle_id), 0) WHERE is_solved = 1"),
+        ],
+    },
+    Migration {
+        version: 10,
+        description: "活动暂停",
+        steps: &[
+            Step::AddColumn { table: "activities", column: "paused_at", decl: "DATETIME" },
+        ],
+    },
+    Migration {
+        version: 11,
+        description: "时间统一以 UTC 保存",
+        steps: &[
+            // 之前的记录与活动时间都按部署所在地的本地时间保存，按配置的活动时区换算
+            Step::ToUtc { table: "users", columns: &["register_time", "token_expires"] },
+            Step::ToUtc { table: "riddles", columns: &["add_time", "shown_at"] },
+            Step::ToUtc { table: "guess_records", columns: &["solve_time"] },
+            Step::ToUtc { table: "activities", columns: &["start_time", "end_time", "paused_at"] },
+        ],
+    },
+    Migration {
+        version: 12,
+        description: "轮次：按计划时间分批发布灯谜",
+        steps: &[
+            Step::Sql(
+                "CREATE TABLE IF NOT EXISTS rounds (
+                    id INTEGER PRIMARY KEY AUTOINCREMENT,
+                    activity_id INTEGER NOT NULL,
+                    name TEXT NOT NULL,
+                    release_time DATETIME NOT NULL,
+                    announced_at DATETIME
+                )",
+            ),
+            Step::Sql("CREATE INDEX IF NOT EXISTS idx_rounds_activity ON rounds (activity_id, release_time)"),
+            Step::AddColumn { table: "riddles", column: "round_id", decl: "INTEGER" },
+        ],
+    },
+    Migration {
+        version: 13,
+        description: "灯谜分类与标签",
+        steps: &[
+            Step::AddColumn { table: "riddles", column: "category", decl: "TEXT NOT NULL DEFAULT ''" },
+            Step::AddColumn { table: "riddles", column: "tags_json", decl: "TEXT NOT NULL DEFAULT '[]'" },
+            Step::Sql("CREATE INDEX IF NOT EXISTS idx_riddles_category ON riddles (activity_id, category)"),
+        ],
+    },
+    Migration {
+        version: 14,
+        description: "逐条揭晓的提示与玩家揭晓记录",
+        steps: &[
+            Step::AddColumn { table: "riddles", column: "hints_json", decl: "TEXT NOT NULL DEFAULT '[]'" },
+            Step::Sql(
+                "CREATE TABLE IF NOT EXISTS hint_reveals (
+                    user_id INTEGER NOT NULL,
+                    riddle_id INTEGER NOT NULL,
+                    hint_index INTEGER NOT NULL,
+                    cost INTEGER NOT NULL DEFAULT 0,
+                    revealed_at DATETIME NOT NULL,
+                    PRIMARY KEY (user_id, riddle_id, hint_index)
+                )",
+            ),
+        ],
+    },
+    Migration {
+        version: 15,
+        description: "图片、音频灯谜",
+        steps: &[
+            Step::AddColumn { table: "riddles", column: "media_type", decl: "TEXT NOT NULL DEFAULT 'none'" },
+            Step::AddColumn { table: "riddles", column: "media_url", decl: "TEXT NOT NULL DEFAULT ''" },
+        ],
+    },
+    Migration {
+        version: 16,
+        description: "灯谜导入编号",
+        steps: &[
+            Step::AddColumn { table: "riddles", column: "external_id", decl: "TEXT NOT NULL DEFAULT ''" },
+            Step::Sql("CREATE INDEX IF NOT EXISTS idx_riddles_external_id ON riddles (activity_id, external_id)"),
+        ],
+    },
+    Migration {
+        version: 17,
+        description: "选项顺序与打乱种子",
+        steps: &[
+            Step::AddColumn { table: "riddles", column: "option_order", decl: "TEXT NOT NULL DEFAULT 'keep'" },
+            Step::AddColumn { table: "riddles", column: "option_seed", decl: "INTEGER NOT NULL DEFAULT 0" },
+        ],
+    },
+    Migration {
+        version: 18,
+        description: "导入列映射",
+        steps: &[
+            Step::Sql(
+                "CREATE TABLE IF NOT EXISTS import_mappings (
+                    id INTEGER PRIMARY KEY AUTOINCREMENT,
+                    name TEXT NOT NULL UNIQUE,
+                    mapping_json TEXT NOT NULL DEFAULT '{}',
+                    updated_at DATETIME NOT NULL
+                )",
+            ),
+        ],
+    },
+];
+
+/// 当前代码对应的最新数据库版本
+pub fn latest_version() -> i64 {
+    MIGRATIONS.last().map(|m| m.version).unwrap_or(0)
+}
+
+/// 连接 SQLite 数据库，文件不存在时自动创建 (mode=rwc)
+pub async fn connect(db_path: &Path) -> Result<SqlitePool, sqlx::Error> {
+    let db_url = format!("sqlite:{}?mode=rwc", db_path.display());
+    tracing::info!("Connecting to database: {}", db_url);
+
+    // 显式设置 SQLite 的繁忙超时，对连接池中的每个连接都生效
+    let options = SqliteConnectOptions::from_str(&db_url)?.busy_timeout(Duration::from_secs(5));
+
+    SqlitePoolOptions::new()
+        .max_connections(5)
+        .acquire_timeout(Duration::from_secs(5))
+        .connect_with(options)
+        .await
+}
+
+/// 数据库当前版本，未执行过任何迁移的数据库 (包括旧版本直接建表的 lantern.db) 为 0
+pub async fn current_version(pool: &SqlitePool) -> Result<i64, sqlx::Error> {
+    ensure_version_table(pool).await?;
+    let (version,): (Option<i64>,) = sqlx::query_as("SELECT MAX(version) FROM schema_version")
+        .fetch_one(pool)
+        .await?;
+    Ok(version.unwrap_or(0))
+}
+
+async fn ensure_version_table(pool: &SqlitePool) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "CREATE TABLE IF NOT EXISTS schema_version (
+            version INTEGER PRIMARY KEY,
+            description TEXT NOT NULL,
+            applied_at DATETIME DEFAULT CURRENT_TIMESTAMP
+        )"
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// 依次执行尚未应用的迁移，每个迁移在独立事务中完成，返回迁移后的版本号
+///
+/// `timezone` 为旧数据中本地时间所在的时区，换算为 UTC 时使用
+pub async fn migrate(pool: &SqlitePool, timezone: FixedOffset) -> Result<i64, sqlx::Error> {
+    let mut version = current_version(pool).await?;
+    if version > latest_version() {
+        tracing::warn!("数据库版本 {} 高于当前程序支持的版本 {}，请升级程序", version, latest_version());
+        return Ok(version);
+    }
+
+    let pending = MIGRATIONS.iter().filter(|m| m.version > version).collect::<Vec<_>>();
+    for migration in pending {
+        tracing::info!("Applying migration v{}: {}", migration.version, migration.description);
+        let mut tx = pool.begin().await?;
+        for step in migration.steps {
+            match step {
+                Step::Sql(sql) => {
+                    sqlx::query(sql).execute(&mut *tx).await?;
+                }
+                Step::AddColumn { table, column, decl } => {
+                    add_column_if_missing(&mut tx, table, column, decl).await?;
+                }
+                Step::ToUtc { table, columns } => {
+                    // 本地时间减去时区偏移即为 UTC，为 NULL 的列保持不变
+                    let shift = format!("{:+} seconds", -timezone.local_minus_utc());
+                    let sets: Vec<String> = columns.iter().map(|c| format!("{0} = datetime({0}, '{1}')", c, shift)).collect();
+                    sqlx::query(&format!("UPDATE {} SET {}", table, sets.join(", "))).execute(&mut *tx).await?;
+                }
+            }
+        }
+        sqlx::query("INSERT INTO schema_version (version, description) VALUES (?, ?)")
+            .bind(migration.version)
+            .bind(migration.description)
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+        version = migration.version;
+    }
+
+    Ok(version)
+}
+
+async fn add_column_if_missing(conn: &mut SqliteConnection, table: &str, column: &str, decl: &str) -> Result<(), sqlx::Error> {
+    let columns: Vec<(String,)> = sqlx::query_as(&format!("SELECT name FROM pragma_table_info('{}')", table))
+        .fetch_all(&mut *conn)
+        .await?;
+    if !columns.iter().any(|(name,)| name == column) {
+        sqlx::query(&format!("ALTER TABLE {} ADD COLUMN {} {}", table, column, decl))
+            .execute(&mut *conn)
+            .await?;
+    }
+    Ok(())
+}
@@ -0,0 +1,450 @@
+use chrono::{FixedOffset, NaiveDateTime};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use socketioxide::{extract::{AckSender, Data, SocketRef, TryData}, handler::ConnectHandler, SocketIo};
+use sqlx::SqlitePool;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+use std::time::Duration;
+use crate::{activity, auth, rounds, scoring, AppState};
+use crate::auth::AdminSession;
+use crate::models::ActivityState;
+use crate::utils::{format_local, now_utc, to_local};
+
+// 服务端推送的事件，payload 结构见下方同名结构体与 admin/api_docs.md
+pub const RIDDLE_SOLVED: &str = "riddle_solved";
+pub const LEADERBOARD_UPDATED: &str = "leaderboard_updated";
+pub const RIDDLE_PUBLISHED: &str = "riddle_published";
+pub const RIDDLE_RESET: &str = "riddle_reset";
+pub const USER_DELETED: &str = "user_deleted";
+pub const ACTIVITY_STATE: &str = "activity_state";
+pub const ROUND_OPENED: &str = "round_opened";
+
+/// 客户端切换所在活动：`socket.emit("join_activity", { activity_id: 2 })`
+pub const JOIN_ACTIVITY: &str = "join_activity";
+
+/// `round_opened`：轮次到达发布时间，其中的灯谜开始开放，同时推送给大屏与玩家手机
+#[derive(Debug, Serialize)]
+pub struct RoundOpened {
+    pub activity_id: i64,
+    pub round_id: i64,
+    pub name: String,
+    pub riddle_ids: Vec<i64>,
+}
+
+/// 管理后台命名空间推送的事件
+pub const GUESS_ATTEMPT: &str = "guess_attempt";
+pub const PRESENCE: &str = "presence";
+pub const BANNER: &str = "banner";
+
+/// 管理后台向玩家手机发送横幅：`socket.emit("broadcast_banner", { message }, ack)`
+pub const BROADCAST_BANNER: &str = "broadcast_banner";
+
+/// 管理后台命名空间，连接时需要管理员 token
+pub const ADMIN_NAMESPACE: &str = "/admin";
+
+/// 所有玩家手机所在的房间
+const PLAYERS_ROOM: &str = "players";
+
+/// 横幅最长字数与默认显示时长
+const MAX_BANNER_LEN: usize = 200;
+const DEFAULT_BANNER_SECS: u64 = 10;
+
+/// 活动房间名，大屏只接收所在活动的事件
+pub fn room(activity_id: i64) -> String {
+    format!("activity:{}", activity_id)
+}
+
+/// 某个活动的玩家手机所在的房间
+fn players_room(activity_id: i64) -> String {
+    format!("players:{}", activity_id)
+}
+
+/// 连接的身份：大屏 (默认) 或玩家手机
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ClientRole {
+    #[default]
+    Screen,
+    Player,
+}
+
+/// 连接时的认证数据 `io({ auth: { activity_id: 2, role: "player" } })`，未指定活动时加入当前活动的房间
+#[derive(Debug, Deserialize)]
+pub struct JoinActivity {
+    pub activity_id: Option<i64>,
+    #[serde(default)]
+    pub role: ClientRole,
+}
+
+/// 管理后台连接的认证数据 `io("/admin", { auth: { token } })`，桌面端本机访问可以不带 token
+#[derive(Debug, Deserialize)]
+pub struct AdminAuth {
+    pub token: Option<String>,
+}
+
+/// `broadcast_banner` 的参数，指定活动时只发给该活动的玩家
+#[derive(Debug, Deserialize)]
+pub struct BroadcastBanner {
+    pub message: String,
+    pub activity_id: Option<i64>,
+    pub duration_secs: Option<u64>,
+}
+
+/// `riddle_solved`：灯谜被抢答成功
+#[derive(Debug, Serialize)]
+pub struct RiddleSolved {
+    pub activity_id: Option<i64>,
+    pub riddle_id: i64,
+    pub solver_id: i64,
+    pub solver_name: String,
+    pub solver_avatar: Option<String>,
+    /// 本次获得的积分
+    pub points: i64,
+}
+
+/// `riddle_published`：有新灯谜可以展示 (新增或导入)
+#[derive(Debug, Serialize)]
+pub struct RiddlePublished {
+    pub activity_id: Option<i64>,
+    pub riddle_ids: Vec<i64>,
+}
+
+/// `riddle_reset`：管理员把灯谜重置为未猜中，原中奖记录作废
+#[derive(Debug, Serialize)]
+pub struct RiddleReset {
+    pub activity_id: Option<i64>,
+    pub riddle_id: i64,
+}
+
+/// `user_deleted`：玩家被删除，其抢到的灯谜重新变为未猜中
+#[derive(Debug, Serialize)]
+pub struct UserDeleted {
+    pub activity_id: Option<i64>,
+    pub user_id: i64,
+    pub reset_riddle_ids: Vec<i64>,
+}
+
+/// `activity_state`：活动开始、结束等状态变化
+#[derive(Debug, Serialize)]
+pub struct ActivityStateChanged {
+    pub activity_id: i64,
+    pub name: String,
+    pub state: ActivityState,
+    pub previous: ActivityState,
+    /// 起止时间为活动时区的本地时间
+    pub start_time: NaiveDateTime,
+    pub end_time: NaiveDateTime,
+}
+
+/// `guess_attempt`：玩家的每一次作答 (答对、手慢或答错)，只推送给管理后台
+#[derive(Debug, Serialize)]
+pub struct GuessAttempt {
+    pub activity_id: Option<i64>,
+    pub riddle_id: i64,
+    pub riddle_question: String,
+    pub user_id: i64,
+    pub username: String,
+    pub answer: String,
+    pub normalized_answer: String,
+    pub is_correct: bool,
+    /// `solved` 抢答成功、`too_late` 答对但已被抢先、`wrong` 答错
+    pub result: &'static str,
+    pub points: i64,
+    pub time: String,
+}
+
+/// `presence`：当前在线的玩家手机、大屏与管理后台数量
+#[derive(Debug, Serialize)]
+pub struct Presence {
+    pub players: usize,
+    pub screens: usize,
+    pub admins: usize,
+    pub by_activity: Vec<ActivityPresence>,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct ActivityPresence {
+    pub activity_id: i64,
+    pub players: usize,
+    pub screens: usize,
+}
+
+/// `banner`：管理员发给玩家手机的横幅消息
+#[derive(Debug, Serialize)]
+pub struct Banner {
+    pub message: String,
+    pub activity_id: Option<i64>,
+    pub duration_secs: u64,
+    pub from: String,
+    pub sent_at: String,
+}
+
+/// 注册命名空间：
+/// - `/`：大屏与玩家手机，连接时按认证数据加入活动房间，之后可通过 `join_activity` 切换
+/// - `/admin`：管理后台，需要管理员 token，接收作答流与在线人数，可以发送横幅
+pub fn register(io: &SocketIo, state: Arc<AppState>) {
+    let db = state.db.clone();
+    let screen_io = io.clone();
+    io.ns("/", move |socket: SocketRef, TryData(auth): TryData<JoinActivity>| {
+        let db = db.clone();
+        let io = screen_io.clone();
+        async move {
+            tracing::debug!("A client connected: {:?}", socket.id);
+            let (activity_id, role) = auth.map(|a| (a.activity_id, a.role)).unwrap_or((None, ClientRole::Screen));
+            join_activity(&socket, &db, activity_id, role).await;
+            emit_presence(&io, None);
+
+            let join_io = io.clone();
+            socket.on(JOIN_ACTIVITY, move |socket: SocketRef, Data(payload): Data<JoinActivity>| {
+                let db = db.clone();
+                let io = join_io.clone();
+                async move {
+                    join_activity(&socket, &db, payload.activity_id, payload.role).await;
+                    emit_presence(&io, None);
+                }
+            });
+            socket.on_disconnect(move |socket: SocketRef| {
+                emit_presence(&io, Some(socket.id.to_string()));
+            });
+        }
+    });
+
+    let auth_state = state.clone();
+    let tz = state.config.timezone;
+    let admin_handler = move |socket: SocketRef| {
+        let io = state.io.clone();
+        async move {
+            // 握手中间件已校验并写入当前管理员
+            let session = socket.extensions.get::<AdminSession>().map(|s| s.value().clone());
+            let session = match session {
+                Some(s) => s,
+                None => {
+                    let _ = socket.disconnect();
+                    return;
+                }
+            };
+            tracing::debug!("Admin {} connected to /admin: {:?}", session.username, socket.id);
+            emit_presence(&io, None);
+
+            let banner_io = io.clone();
+            socket.on(BROADCAST_BANNER, move |Data(payload): Data<BroadcastBanner>, ack: AckSender| {
+                let result = broadcast_banner(&banner_io, &session.username, payload, tz);
+                let _ = ack.send(result);
+            });
+            socket.on_disconnect(move |socket: SocketRef| {
+                emit_presence(&io, Some(socket.id.to_string()));
+            });
+        }
+    };
+    // 握手时校验，未登录的连接会收到 connect_error
+    io.ns(ADMIN_NAMESPACE, admin_handler.with(move |socket: SocketRef, TryData(auth): TryData<AdminAuth>| {
+        let state = auth_state.clone();
+        async move {
+            let token = auth.ok().and_then(|a| a.token);
+            match auth::socket_admin(&state, socket.req_parts(), token).await {
+                Some(session) => {
+                    socket.extensions.insert(session);
+                    Ok(())
+                }
+                None => Err("请先登录管理后台"),
+            }
+        }
+    }));
+}
+
+async fn join_activity(socket: &SocketRef, db: &SqlitePool, activity_id: Option<i64>, role: ClientRole) {
+    let activity_id = match activity_id {
+        Some(id) => Some(id),
+        None => activity::active_id(db).await,
+    };
+    // 一个连接只属于一个活动，玩家手机不接收大屏事件
+    let joined: Vec<_> = socket
+        .rooms()
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|r| r.starts_with("activity:") || r.starts_with("players"))
+        .collect();
+    let _ = socket.leave(joined);
+    match (role, activity_id) {
+        (ClientRole::Screen, Some(id)) => {
+            let _ = socket.join(room(id));
+        }
+        (ClientRole::Screen, None) => {}
+        (ClientRole::Player, Some(id)) => {
+            let _ = socket.join(vec![PLAYERS_ROOM.to_string(), players_room(id)]);
+        }
+        (ClientRole::Player, None) => {
+            let _ = socket.join(PLAYERS_ROOM);
+        }
+    }
+}
+
+/// 统计在线连接并推送给管理后台；`leaving` 为正在断开的连接，不计入
+fn emit_presence(io: &SocketIo, leaving: Option<String>) {
+    let is_leaving = |id: String| leaving.as_deref() == Some(id.as_str());
+    let mut presence = Presence { players: 0, screens: 0, admins: 0, by_activity: Vec::new() };
+    let mut by_activity: BTreeMap<i64, ActivityPresence> = BTreeMap::new();
+    for socket in io.sockets().unwrap_or_default() {
+        if is_leaving(socket.id.to_string()) {
+            continue;
+        }
+        let rooms = socket.rooms().unwrap_or_default();
+        let is_player = rooms.iter().any(|r| r == PLAYERS_ROOM);
+        if is_player {
+            presence.players += 1;
+        } else {
+            presence.screens += 1;
+        }
+        let activity_id = rooms
+            .iter()
+            .find_map(|r| r.strip_prefix("activity:").or_else(|| r.strip_prefix("players:")))
+            .and_then(|id| id.parse::<i64>().ok());
+        if let Some(id) = activity_id {
+            let entry = by_activity.entry(id).or_insert_with(|| ActivityPresence { activity_id: id, ..Default::default() });
+            if is_player {
+                entry.players += 1;
+            } else {
+                entry.screens += 1;
+            }
+        }
+    }
+    presence.by_activity = by_activity.into_values().collect();
+
+    presence.admins = io
+        .of(ADMIN_NAMESPACE)
+        .and_then(|op| op.sockets().ok())
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|s| !is_leaving(s.id.to_string()))
+        .count();
+    let admin = match io.of(ADMIN_NAMESPACE) {
+        Some(op) => op,
+        None => return,
+    };
+    if let Err(e) = admin.emit(PRESENCE, presence) {
+        tracing::warn!("Failed to emit {}: {}", PRESENCE, e);
+    }
+}
+
+/// 发送横幅到玩家手机，返回给管理后台的确认
+fn broadcast_banner(io: &SocketIo, from: &str, payload: BroadcastBanner, tz: FixedOffset) -> serde_json::Value {
+    let message = payload.message.trim();
+    if message.is_empty() {
+        return json!({ "code": 400, "message": "横幅内容不能为空" });
+    }
+    if message.chars().count() > MAX_BANNER_LEN {
+        return json!({ "code": 400, "message": format!("横幅内容不能超过 {} 个字", MAX_BANNER_LEN) });
+    }
+
+    let banner = Banner {
+        message: message.to_string(),
+        activity_id: payload.activity_id,
+        duration_secs: payload.duration_secs.filter(|&s| s > 0).unwrap_or(DEFAULT_BANNER_SECS),
+        from: from.to_string(),
+        sent_at: format_local(now_utc(), tz),
+    };
+    let result = match payload.activity_id {
+        Some(id) => io.to(players_room(id)).emit(BANNER, banner),
+        None => io.to(PLAYERS_ROOM).emit(BANNER, banner),
+    };
+    match result {
+        Ok(_) => json!({ "code": 200, "message": "横幅已发送" }),
+        Err(e) => json!({ "code": 500, "message": format!("发送失败: {}", e) }),
+    }
+}
+
+/// 推送到活动房间；不属于任何活动的灯谜推送给所有连接
+pub fn emit<T: Serialize>(io: &SocketIo, activity_id: Option<i64>, event: &'static str, payload: T) {
+    let result = match activity_id {
+        Some(id) => io.to(room(id)).emit(event, payload),
+        None => io.emit(event, payload),
+    };
+    if let Err(e) = result {
+        tracing::warn!("Failed to emit {}: {}", event, e);
+    }
+}
+
+/// 推送一次作答到管理后台
+pub fn guess_attempt(io: &SocketIo, attempt: GuessAttempt) {
+    if let Some(admin) = io.of(ADMIN_NAMESPACE) {
+        if let Err(e) = admin.emit(GUESS_ATTEMPT, attempt) {
+            tracing::warn!("Failed to emit {}: {}", GUESS_ATTEMPT, e);
+        }
+    }
+}
+
+/// `leaderboard_updated`：推送活动排行榜前 10 名，payload 同 `GET /frontend/leaderboard`
+pub async fn leaderboard_changed(state: &AppState, activity_id: Option<i64>) {
+    let act = match activity_id {
+        Some(id) => activity::find(&state.db, id).await,
+        None => None,
+    };
+    let act = match act {
+        Some(a) => a,
+        None => return,
+    };
+    match scoring::leaderboard(&state.db, &act, scoring::DEFAULT_LEADERBOARD_SIZE, state.config.timezone).await {
+        Ok(data) => emit(&state.io, Some(act.id), LEADERBOARD_UPDATED, data),
+        Err(e) => tracing::warn!("Failed to load leaderboard for activity {}: {}", act.id, e),
+    }
+}
+
+/// 后台任务：每秒检查各活动的状态，开始或结束时推送 `activity_state`
+pub fn spawn_state_watcher(state: Arc<AppState>) {
+    tokio::spawn(async move {
+        let mut known: HashMap<i64, ActivityState> = HashMap::new();
+        let mut ticker = tokio::time::interval(Duration::from_secs(1));
+        loop {
+            ticker.tick().await;
+            let now = now_utc();
+            let mut current = HashMap::new();
+            for act in activity::all(&state.db).await {
+                let new_state = activity::state_at(&act, now);
+                if let Some(&previous) = known.get(&act.id) {
+                    if previous != new_state {
+                        emit(&state.io, Some(act.id), ACTIVITY_STATE, ActivityStateChanged {
+                            activity_id: act.id,
+                            name: act.name.clone(),
+                            state: new_state,
+                            previous,
+                            start_time: to_local(act.start_time, state.config.timezone),
+                            end_time: to_local(act.end_time, state.config.timezone),
+                        });
+                    }
+                }
+                current.insert(act.id, new_state);
+            }
+            known = current;
+        }
+    });
+}
+
+/// 后台任务：每秒检查到达发布时间的轮次，推送 `round_opened`；每个轮次只推送一次
+pub fn spawn_round_scheduler(state: Arc<AppState>) {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(Duration::from_secs(1));
+        loop {
+            ticker.tick().await;
+            let now = now_utc();
+            for round in rounds::due(&state.db, now).await {
+                if !rounds::mark_announced(&state.db, round.id, now).await {
+                    continue;
+                }
+                let payload = RoundOpened {
+                    activity_id: round.activity_id,
+                    round_id: round.id,
+                    name: round.name.clone(),
+                    riddle_ids: rounds::unsolved_riddle_ids(&state.db, round.id).await,
+                };
+                tracing::info!("Round {} ({}) opened", round.id, round.name);
+                let result = state.io
+                    .to(vec![room(round.activity_id), players_room(round.activity_id)])
+                    .emit(ROUND_OPENED, payload);
+                if let Err(e) = result {
+                    tracing::warn!("Failed to emit {}: {}", ROUND_OPENED, e);
+                }
+            }
+        }
+    });
+}
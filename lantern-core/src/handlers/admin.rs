@@ -0,0 +1,1343 @@
+use axum::{
+    extract::{Extension, Path, Query, State, Multipart},
+    http::StatusCode,
+    response::{Html, IntoResponse, Json},
+};
+use serde::{Deserialize};
+use std::sync::Arc;
+use crate::{AppState, activity, answer, auth::{self, AdminSession}, catalog, events, hints, importer, mappings, media, models::*, options, sheet, rounds, scoring, utils::{format_local, now_utc, to_local, to_utc}};
+use crate::query::{Arg, ListQuery, Page, parse_bool_param, parse_datetime_param};
+use chrono::{FixedOffset, NaiveDateTime, Timelike};
+use serde_json::json;
+
+#[derive(Deserialize)]
+pub struct PaginationParams {
+    pub page: Option<u32>,
+    #[serde(rename = "pageSize")]
+    pub page_size: Option<u32>,
+    pub keyword: Option<String>,
+    /// 是否已被猜中 (`1`/`0`)，仅灯谜列表使用
+    pub solved: Option<String>,
+    /// 时间范围：`YYYY-MM-DD` 或 `YYYY-MM-DD HH:MM:SS`
+    pub start_date: Option<String>,
+    pub end_date: Option<String>,
+    /// 排序字段，取值见各接口的白名单
+    pub sort: Option<String>,
+    /// `asc` 或 `desc` (默认)
+    pub order: Option<String>,
+    /// 所属活动，未指定时为当前活动；用户列表不区分活动
+    pub activity_id: Option<i64>,
+    /// 作答记录：按灯谜过滤
+    pub riddle_id: Option<i64>,
+    /// 灯谜列表：按轮次过滤
+    pub round_id: Option<i64>,
+    /// 灯谜列表：按分类、标签、难度过滤
+    pub category: Option<String>,
+    pub tag: Option<String>,
+    pub difficulty: Option<i64>,
+    /// 作答记录：答案是否正确 (`1`/`0`)
+    pub correct: Option<String>,
+}
+
+fn page_response<T: serde::Serialize>(result: Result<Page<T>, sqlx::Error>) -> axum::response::Response {
+    match result {
+        Ok(page) => Json(json!({ "code": 200, "message": "success", "data": page })).into_response(),
+        Err(e) => Json(json!({ "code": 500, "message": format!("查询失败: {}", e) })).into_response(),
+    }
+}
+
+/// 列表接口所属的活动：请求中指定的活动，否则为当前活动
+async fn scoped_activity(state: &AppState, activity_id: Option<i64>) -> Option<i64> {
+    match activity_id {
+        Some(id) => Some(id),
+        None => activity::active_id(&state.db).await,
+    }
+}
+
+/// 全部作答记录，可按玩家昵称、提交的答案或谜面搜索
+fn attempts_query(activity_id: Option<i64>, keyword: Option<&str>, start_date: Option<&str>, end_date: Option<&str>, tz: FixedOffset) -> ListQuery {
+    ListQuery::new(
+        "gr.*, u.username as user_name, r.question as riddle_question, r.answer as riddle_answer",
+        "guess_records gr JOIN users u ON gr.user_id = u.id JOIN riddles r ON gr.riddle_id = r.id",
+    )
+    .eq("r.activity_id", activity_id.map(Arg::Int))
+    .keyword(&["u.username", "gr.answer", "r.question"], keyword)
+    .date_range("gr.solve_time", parse_datetime_param(start_date, false, tz), parse_datetime_param(end_date, true, tz))
+}
+
+/// 中奖记录：抢到灯谜的作答
+fn leaderboard_query(activity_id: Option<i64>, keyword: Option<&str>, start_date: Option<&str>, end_date: Option<&str>, tz: FixedOffset) -> ListQuery {
+    ListQuery::new(
+        "gr.*, u.username as user_name, r.question as riddle_question, r.answer as riddle_answer",
+        "guess_records gr JOIN users u ON gr.user_id = u.id JOIN riddles r ON gr.riddle_id = r.id",
+    )
+    .filter("gr.is_solved = 1", vec![])
+    .eq("r.activity_id", activity_id.map(Arg::Int))
+    .keyword(&["u.username"], keyword)
+    .date_range("gr.solve_time", parse_datetime_param(start_date, false, tz), parse_datetime_param(end_date, true, tz))
+}
+
+/// 非空的文本过滤参数
+fn text_param(value: Option<&str>) -> Option<Arg> {
+    value.map(str::trim).filter(|v| !v.is_empty()).map(|v| Arg::Text(v.to_string()))
+}
+
+/// 活动的 JSON 表示，附带解析后的难度系数数组与当前状态，时间按活动时区显示
+fn activity_json(act: &Activity, tz: FixedOffset) -> serde_json::Value {
+    let mut val = json!(act);
+    val["start_time"] = json!(format_local(act.start_time, tz));
+    val["end_time"] = json!(format_local(act.end_time, tz));
+    val["paused_at"] = json!(act.paused_at.map(|t| format_local(t, tz)));
+    val["difficulty_multipliers"] = json!(scoring::parse_multipliers(&act.difficulty_multipliers_json));
+    val["state"] = json!(activity::state_at(act, now_utc()));
+    val
+}
+
+/// CSV 字段：加引号并转义；以 `= + - @` 开头的内容前加 `'`，防止在表格软件中被当作公式
+fn csv_field(value: &str) -> String {
+    let value = if value.starts_with(['=', '+', '-', '@']) {
+        format!("'{}", value)
+    } else {
+        value.to_string()
+    };
+    format!("\"{}\"", value.replace('"', "\"\""))
+}
+
+pub async fn get_admin_index(
+    State(state): State<Arc<AppState>>,
+) -> impl IntoResponse {
+    let ctx = tera::Context::new();
+    match state.tera.render("admin/index.html", &ctx) {
+        Ok(html) => Html(html).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, format!("Template error: {}", e)).into_response(),
+    }
+}
+
+#[derive(Deserialize)]
+pub struct AdminLoginPayload {
+    pub username: String,
+    pub password: String,
+}
+
+pub async fn login(
+    State(state): State<Arc<AppState>>,
+    Json(payload): Json<AdminLoginPayload>,
+) -> impl IntoResponse {
+    let admin: Option<Admin> = sqlx::query_as("SELECT * FROM admins WHERE username = ?")
+        .bind(payload.username.trim())
+        .fetch_optional(&state.db)
+        .await
+        .unwrap_or(None);
+
+    match admin {
+        Some(a) if auth::verify_password(&payload.password, &a.password_hash) => {
+            let token = auth::issue_admin_token(&state.session_key, a.id);
+            Json(json!({
+                "code": 200,
+                "message": "登录成功",
+                "data": {
+                    "token": token,
+                    "expires_in": auth::ADMIN_SESSION_TTL_SECS,
+                    "admin": a
+                }
+            })).into_response()
+        }
+        _ => (StatusCode::UNAUTHORIZED, Json(json!({ "code": 401, "message": "用户名或密码错误" }))).into_response(),
+    }
+}
+
+pub async fn get_me(
+    Extension(session): Extension<AdminSession>,
+) -> impl IntoResponse {
+    Json(json!({ "code": 200, "message": "success", "data": session }))
+}
+
+pub async fn get_admins(
+    State(state): State<Arc<AppState>>,
+) -> impl IntoResponse {
+    let admins: Vec<Admin> = sqlx::query_as("SELECT * FROM admins ORDER BY id").fetch_all(&state.db).await.unwrap_or_default();
+    Json(json!({ "code": 200, "message": "success", "data": admins }))
+}
+
+#[derive(Deserialize)]
+pub struct AdminUpsertPayload {
+    pub username: String,
+    pub password: String,
+    pub role: Option<AdminRole>,
+}
+
+pub async fn create_admin(
+    State(state): State<Arc<AppState>>,
+    Json(payload): Json<AdminUpsertPayload>,
+) -> impl IntoResponse {
+    let username = payload.username.trim();
+    if username.is_empty() || payload.password.len() < 6 {
+        return Json(json!({ "code": 400, "message": "用户名不能为空，密码至少 6 位" })).into_response();
+    }
+
+    let result = sqlx::query("INSERT INTO admins (username, password_hash, role, create_time) VALUES (?, ?, ?, ?)")
+        .bind(username)
+        .bind(auth::hash_password(&payload.password))
+        .bind(payload.role.unwrap_or(AdminRole::Operator))
+        .bind(now_utc())
+        .execute(&state.db)
+        .await;
+
+    match result {
+        Ok(res) => {
+            let admin: Option<Admin> = sqlx::query_as("SELECT * FROM admins WHERE id = ?")
+                .bind(res.last_insert_rowid()).fetch_optional(&state.db).await.unwrap_or(None);
+            Json(json!({ "code": 200, "message": "创建成功", "data": admin })).into_response()
+        }
+        Err(_) => Json(json!({ "code": 400, "message": "用户名已存在" })).into_response(),
+    }
+}
+
+pub async fn delete_admin(
+    State(state): State<Arc<AppState>>,
+    Extension(session): Extension<AdminSession>,
+    Path(id): Path<i64>,
+) -> impl IntoResponse {
+    if id == session.id {
+        return Json(json!({ "code": 400, "message": "不能删除当前登录的账号" })).into_response();
+    }
+
+    // 至少保留一个超级管理员
+    let result = sqlx::query(
+        "DELETE FROM admins WHERE id = ? AND (role != 'super_admin' OR (SELECT COUNT(*) FROM admins WHERE role = 'super_admin') > 1)"
+    )
+    .bind(id)
+    .execute(&state.db)
+    .await;
+    match result {
+        Ok(res) if res.rows_affected() > 0 => Json(json!({ "code": 200, "message": "删除成功" })).into_response(),
+        _ => Json(json!({ "code": 404, "message": "管理员不存在或是最后一个超级管理员" })).into_response(),
+    }
+}
+
+pub async fn get_users(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<PaginationParams>,
+) -> impl IntoResponse {
+    let result = ListQuery::new("*", "users")
+        .keyword(&["username", "user_code"], params.keyword.as_deref())
+        .date_range("register_time", parse_datetime_param(params.start_date.as_deref(), false, state.config.timezone), parse_datetime_param(params.end_date.as_deref(), true, state.config.timezone))
+        .order_by(params.sort.as_deref(), params.order.as_deref(), &[("id", "id"), ("username", "username"), ("register_time", "register_time")], "id")
+        .fetch_page::<User>(&state.db, params.page, params.page_size)
+        .await
+        .map(|page| page.map(|u| {
+            let mut val = json!(u);
+            if let Some(t) = u.register_time {
+                val["register_time"] = json!(format_local(t, state.config.timezone));
+            }
+            val
+        }));
+
+    page_response(result)
+}
+
+pub async fn delete_user(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+) -> impl IntoResponse {
+    // 该用户抢到的灯谜，删除后重新变为未猜中，按活动通知大屏
+    let solved: Vec<(i64, Option<i64>)> = sqlx::query_as("SELECT id, activity_id FROM riddles WHERE solver_id = ?")
+        .bind(id).fetch_all(&state.db).await.unwrap_or_default();
+
+    // 先删除该用户的答题记录
+    let _ = sqlx::query("DELETE FROM guess_records WHERE user_id = ?").bind(id).execute(&state.db).await;
+    let _ = sqlx::query("DELETE FROM hint_reveals WHERE user_id = ?").bind(id).execute(&state.db).await;
+    
+    // 如果该用户是某些灯谜的获胜者，清除灯谜表中的获胜者信息
+    let _ = sqlx::query("UPDATE riddles SET is_solved = 0, solver_id = NULL WHERE solver_id = ?").bind(id).execute(&state.db).await;
+
+    let result = sqlx::query("DELETE FROM users WHERE id = ?").bind(id).execute(&state.db).await;
+    match result {
+        Ok(res) if res.rows_affected() > 0 => {
+            let mut activity_ids: Vec<Option<i64>> = solved.iter().map(|(_, a)| *a).collect();
+            activity_ids.sort();
+            activity_ids.dedup();
+            for activity_id in activity_ids {
+                let reset_riddle_ids = solved.iter().filter(|(_, a)| *a == activity_id).map(|(r, _)| *r).collect();
+                events::emit(&state.io, activity_id, events::USER_DELETED, events::UserDeleted { activity_id, user_id: id, reset_riddle_ids });
+                events::leaderboard_changed(&state, activity_id).await;
+            }
+            Json(json!({ "code": 200, "message": "删除成功" })).into_response()
+        }
+        _ => Json(json!({ "code": 404, "message": "用户不存在或已被删除" })).into_response(),
+    }
+}
+
+pub async fn get_riddles(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<PaginationParams>,
+) -> impl IntoResponse {
+    let activity_id = scoped_activity(&state, params.activity_id).await;
+    let result = ListQuery::new(
+        "r.*, u.username as solver_name, u.avatar as solver_avatar",
+        "riddles r LEFT JOIN users u ON r.solver_id = u.id",
+    )
+    .eq("r.activity_id", activity_id.map(Arg::Int))
+    .keyword(&["r.question", "r.answer"], params.keyword.as_deref())
+    .eq("r.is_solved", parse_bool_param(params.solved.as_deref()).map(Arg::Bool))
+    .eq("r.round_id", params.round_id.map(Arg::Int))
+    .eq("r.category", text_param(params.category.as_deref()))
+    .filter_opt(catalog::HAS_TAG, text_param(params.tag.as_deref()))
+    .eq("r.difficulty", params.difficulty.map(Arg::Int))
+    .date_range("r.add_time", parse_datetime_param(params.start_date.as_deref(), false, state.config.timezone), parse_datetime_param(params.end_date.as_deref(), true, state.config.timezone))
+    .order_by(params.sort.as_deref(), params.order.as_deref(), &[("id", "r.id"), ("add_time", "r.add_time"), ("difficulty", "r.difficulty")], "r.add_time")
+    .fetch_page::<RiddleWithSolver>(&state.db, params.page, params.page_size)
+    .await
+    .map(|page| page.map(|r| {
+        let options: Vec<String> = serde_json::from_str(&r.options_json).unwrap_or_default();
+        let mut val = json!(r);
+        val["options"] = json!(options);
+        val["aliases"] = json!(answer::parse_aliases(&r.aliases_json));
+        val["tags"] = json!(catalog::parse_tags(&r.tags_json));
+        val["hints"] = json!(hints::parse_hints(&r.hints_json));
+        if let Some(t) = r.add_time {
+            val["add_time"] = json!(format_local(t, state.config.timezone));
+        }
+        val
+    }));
+
+    page_response(result)
+}
+
+/// 活动中已有的分类、标签与难度，供筛选与录入时选择
+pub async fn get_riddle_facets(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<PaginationParams>,
+) -> impl IntoResponse {
+    let activity_id = scoped_activity(&state, params.activity_id).await;
+    Json(json!({ "code": 200, "message": "success", "data": catalog::facets(&state.db, activity_id, None).await }))
+}
+
+#[derive(Deserialize)]
+pub struct RiddleUpsertPayload {
+    pub id: Option<i64>,
+    pub question: Option<String>,
+    pub answer: Option<String>,
+    pub remark: Option<String>,
+    pub options: Option<Vec<String>>,
+    /// 选项顺序：`keep` 按录入顺序 (新建时默认) / `shuffle` 每个玩家看到的顺序不同
+    pub option_order: Option<OptionOrder>,
+    /// 备选答案，如 `["北京市", "beijing"]`
+    pub aliases: Option<Vec<String>>,
+    /// 答案匹配方式：`normal` (默认) / `traditional` / `pinyin`
+    pub match_mode: Option<MatchMode>,
+    /// 分值，新建时默认 10
+    pub points: Option<i64>,
+    /// 难度等级 1~5，新建时默认 1
+    pub difficulty: Option<i64>,
+    /// 分类，空字符串表示未分类
+    pub category: Option<String>,
+    /// 标签，如 `["字谜", "三国"]`
+    pub tags: Option<Vec<String>>,
+    /// 逐条揭晓的提示，按顺序排列
+    pub hints: Option<Vec<hints::Hint>>,
+    /// 谜面附带的图片或音频，先通过 `/media` 上传获得地址；空字符串表示纯文字灯谜
+    pub media_url: Option<String>,
+    /// 重置为未猜中，原中奖记录作废且不再计分
+    pub reset_status: Option<serde_json::Value>,
+    /// 所属活动，新建时默认为当前活动
+    pub activity_id: Option<i64>,
+    /// 所属轮次，0 表示不分轮次；修改时不传则保持不变
+    pub round_id: Option<i64>,
+}
+
+/// 校验灯谜引用的媒体地址，返回媒体类型；空地址表示纯文字灯谜
+fn riddle_media(state: &AppState, media_url: &str) -> Result<MediaType, &'static str> {
+    if media_url.is_empty() {
+        return Ok(MediaType::None);
+    }
+    match (media::local_path(&state.config.template_dir, media_url), media::media_type_of(media_url)) {
+        (Some(path), Some(media_type)) if path.is_file() => Ok(media_type),
+        _ => Err("媒体文件不存在，请重新上传"),
+    }
+}
+
+/// 上传灯谜的图片或音频，返回媒体类型与地址，保存灯谜时填入 `media_url`
+pub async fn upload_media(
+    State(state): State<Arc<AppState>>,
+    mut multipart: Multipart,
+) -> impl IntoResponse {
+    while let Ok(Some(field)) = multipart.next_field().await {
+        if field.name() != Some("file") {
+            continue;
+        }
+        let filename = field.file_name().unwrap_or_default().to_string();
+        let data = match field.bytes().await {
+            Ok(d) => d,
+            Err(e) => return Json(json!({ "code": 400, "message": format!("上传失败: {}", e) })).into_response(),
+        };
+        let date_dir = to_local(now_utc(), state.config.timezone).format("%Y/%m/%d").to_string();
+        return match media::save(&state.config.template_dir, &date_dir, &filename, &data).await {
+            Ok((media_type, media_url)) => Json(json!({ "code": 200, "message": "上传成功", "data": { "media_type": media_type, "media_url": media_url } })).into_response(),
+            Err(msg) => Json(json!({ "code": 400, "message": msg })).into_response(),
+        };
+    }
+    Json(json!({ "code": 400, "message": "未上传文件" })).into_response()
+}
+
+/// 校验灯谜所属的活动必须存在，为空表示不属于任何活动
+async fn riddle_activity(state: &AppState, activity_id: Option<i64>) -> Result<Option<i64>, &'static str> {
+    match activity_id {
+        Some(id) if activity::find(&state.db, id).await.is_none() => Err("活动不存在"),
+        _ => Ok(activity_id),
+    }
+}
+
+/// 校验灯谜的轮次：轮次必须属于灯谜所在的活动，`Some(0)` 表示取消轮次；灯谜换到其他活动时原轮次失效
+async fn riddle_round(state: &AppState, round_id: Option<i64>, current: Option<i64>, activity_id: Option<i64>) -> Result<Option<i64>, &'static str> {
+    let round_id = match round_id {
+        Some(0) => return Ok(None),
+        Some(id) => id,
+        None => match current {
+            Some(id) => id,
+            None => return Ok(None),
+        },
+    };
+    match rounds::find(&state.db, round_id).await {
+        Some(round) if Some(round.activity_id) == activity_id => Ok(Some(round.id)),
+        Some(_) => Err("轮次不属于灯谜所在的活动"),
+        None => Err("轮次不存在"),
+    }
+}
+
+pub async fn upsert_riddle(
+    State(state): State<Arc<AppState>>,
+    Json(payload): Json<RiddleUpsertPayload>,
+) -> impl IntoResponse {
+    if let Some(id) = payload.id {
+        let riddle: Option<Riddle> = sqlx::query_as("SELECT * FROM riddles WHERE id = ?").bind(id).fetch_optional(&state.db).await.unwrap_or(None);
+        if let Some(r) = riddle {
+            let question = payload.question.unwrap_or(r.question);
+            let answer = payload.answer.unwrap_or(r.answer);
+            let aliases_json = payload.aliases
+                .map(|a| serde_json::to_string(&answer::clean_aliases(&answer, a)).unwrap())
+                .unwrap_or(r.aliases_json);
+            let remark = payload.remark.unwrap_or(r.remark.unwrap_or_default());
+            // 有选项时保证正确答案在其中
+            let options = payload.options.unwrap_or_else(|| serde_json::from_str(&r.options_json).unwrap_or_default());
+            let option_order = payload.option_order.unwrap_or(r.option_order);
+            let options_json = serde_json::to_string(&options::with_answer(&answer, options, option_order, r.option_seed).0).unwrap();
+            let activity_id = match riddle_activity(&state, payload.activity_id.or(r.activity_id)).await {
+                Ok(id) => id,
+                Err(msg) => return Json(json!({ "code": 400, "message": msg })).into_response(),
+            };
+            let match_mode = payload.match_mode.unwrap_or(r.match_mode);
+            let points = payload.points.map(|p| p.max(0)).unwrap_or(r.points);
+            let difficulty = payload.difficulty.map(|d| d.clamp(scoring::MIN_DIFFICULTY, scoring::MAX_DIFFICULTY)).unwrap_or(r.difficulty);
+            let category = payload.category.map(|c| catalog::clean_category(&c)).unwrap_or(r.category);
+            let tags_json = payload.tags.map(|t| serde_json::to_string(&catalog::clean_tags(t)).unwrap()).unwrap_or(r.tags_json);
+            let hints_json = payload.hints.map(|h| serde_json::to_string(&hints::clean_hints(h)).unwrap()).unwrap_or(r.hints_json);
+            let media_url = payload.media_url.map(|u| u.trim().to_string()).unwrap_or_else(|| r.media_url.clone());
+            let media_type = match riddle_media(&state, &media_url) {
+                Ok(t) => t,
+                Err(msg) => return Json(json!({ "code": 400, "message": msg })).into_response(),
+            };
+            let round_id = match riddle_round(&state, payload.round_id, r.round_id.filter(|_| activity_id == r.activity_id), activity_id).await {
+                Ok(id) => id,
+                Err(msg) => return Json(json!({ "code": 400, "message": msg })).into_response(),
+            };
+            
+            let mut is_solved = r.is_solved;
+            let mut solver_id = r.solver_id;
+            let mut shown_at = r.shown_at;
+            let mut reset = false;
+            
+            if let Some(reset_status) = payload.reset_status {
+                if reset_status == true || reset_status == "true" {
+                    is_solved = false;
+                    solver_id = None;
+                    shown_at = None;
+                    reset = true;
+                }
+            }
+
+            let saved = sqlx::query("UPDATE riddles SET question = ?, answer = ?, aliases_json = ?, match_mode = ?, points = ?, difficulty = ?, remark = ?, options_json = ?, option_order = ?, is_solved = ?, solver_id = ?, shown_at = ?, activity_id = ?, round_id = ?, category = ?, tags_json = ?, hints_json = ?, media_type = ?, media_url = ? WHERE id = ?")
+                .bind(question).bind(answer).bind(aliases_json).bind(match_mode).bind(points).bind(difficulty).bind(remark).bind(options_json).bind(option_order).bind(is_solved).bind(solver_id).bind(shown_at).bind(activity_id).bind(round_id).bind(category).bind(tags_json).bind(hints_json).bind(media_type).bind(&media_url).bind(id)
+                .execute(&state.db).await;
+            if let Err(e) = saved {
+                return Json(json!({ "code": 500, "message": format!("保存失败: {}", e) })).into_response();
+            }
+            if media_url != r.media_url {
+                media::remove_unused(&state.db, &state.config.template_dir, &[r.media_url]).await;
+            }
+            if reset {
+                // 原中奖记录作废，不再计入排行榜
+                sqlx::query("UPDATE guess_records SET is_solved = 0, points = 0 WHERE riddle_id = ? AND is_solved = 1")
+                    .bind(id).execute(&state.db).await.unwrap_or_default();
+                // 提示重新从展示时开始计时，玩家已揭晓的提示作废
+                sqlx::query("DELETE FROM hint_reveals WHERE riddle_id = ?").bind(id).execute(&state.db).await.unwrap_or_default();
+                events::emit(&state.io, activity_id, events::RIDDLE_RESET, events::RiddleReset { activity_id, riddle_id: id });
+                events::leaderboard_changed(&state, activity_id).await;
+            }
+            
+            // 获取更新后的数据，使用 fetch_optional 避免 Panic
+            let updated: Option<RiddleWithSolver> = sqlx::query_as("SELECT r.*, u.username as solver_name, u.avatar as solver_avatar FROM riddles r LEFT JOIN users u ON r.solver_id = u.id WHERE r.id = ?")
+                .bind(id).fetch_optional(&state.db).await.unwrap_or(None);
+            
+            if let Some(upd) = updated {
+                let options: Vec<String> = serde_json::from_str(&upd.options_json).unwrap_or_default();
+                let mut val = json!(upd);
+                val["options"] = json!(options);
+                val["aliases"] = json!(answer::parse_aliases(&upd.aliases_json));
+                val["tags"] = json!(catalog::parse_tags(&upd.tags_json));
+                val["hints"] = json!(hints::parse_hints(&upd.hints_json));
+                if let Some(t) = upd.add_time {
+                    val["add_time"] = json!(format_local(t, state.config.timezone));
+                }
+                return Json(json!({ "code": 200, "message": "更新成功", "data": val })).into_response();
+            }
+            return Json(json!({ "code": 200, "message": "更新成功(未获取到回显数据)" })).into_response();
+        }
+        Json(json!({ "code": 404, "message": "灯谜不存在" })).into_response()
+    } else {
+        let now = now_utc();
+        let activity_id = match riddle_activity(&state, scoped_activity(&state, payload.activity_id).await).await {
+            Ok(id) => id,
+            Err(msg) => return Json(json!({ "code": 400, "message": msg })).into_response(),
+        };
+        let answer = payload.answer.unwrap_or_default();
+        let option_order = payload.option_order.unwrap_or_default();
+        let option_seed = rand::random::<u32>() as i64;
+        let options_json = serde_json::to_string(&options::with_answer(&answer, payload.options.unwrap_or_default(), option_order, option_seed).0).unwrap();
+        let aliases_json = serde_json::to_string(&answer::clean_aliases(&answer, payload.aliases.unwrap_or_default())).unwrap();
+        let round_id = match riddle_round(&state, payload.round_id, None, activity_id).await {
+            Ok(id) => id,
+            Err(msg) => return Json(json!({ "code": 400, "message": msg })).into_response(),
+        };
+        let media_url = payload.media_url.map(|u| u.trim().to_string()).unwrap_or_default();
+        let media_type = match riddle_media(&state, &media_url) {
+            Ok(t) => t,
+            Err(msg) => return Json(json!({ "code": 400, "message": msg })).into_response(),
+        };
+        let result = sqlx::query("INSERT INTO riddles (question, answer, aliases_json, match_mode, points, difficulty, remark, options_json, option_order, option_seed, add_time, activity_id, round_id, category, tags_json, hints_json, media_type, media_url) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)")
+            .bind(payload.question.unwrap_or_default())
+            .bind(answer)
+            .bind(aliases_json)
+            .bind(payload.match_mode.unwrap_or_default())
+            .bind(payload.points.map(|p| p.max(0)).unwrap_or(scoring::DEFAULT_POINTS))
+            .bind(payload.difficulty.map(|d| d.clamp(scoring::MIN_DIFFICULTY, scoring::MAX_DIFFICULTY)).unwrap_or(scoring::MIN_DIFFICULTY))
+            .bind(payload.remark.unwrap_or_default())
+            .bind(options_json)
+            .bind(option_order)
+            .bind(option_seed)
+            .bind(now)
+            .bind(activity_id)
+            .bind(round_id)
+            .bind(catalog::clean_category(&payload.category.unwrap_or_default()))
+            .bind(serde_json::to_string(&catalog::clean_tags(payload.tags.unwrap_or_default())).unwrap())
+            .bind(serde_json::to_string(&hints::clean_hints(payload.hints.unwrap_or_default())).unwrap())
+            .bind(media_type)
+            .bind(media_url)
+            .execute(&state.db).await;
+        let inserted: Result<Riddle, sqlx::Error> = match result {
+            Ok(r) => sqlx::query_as("SELECT * FROM riddles WHERE id = ?").bind(r.last_insert_rowid()).fetch_one(&state.db).await,
+            Err(e) => Err(e),
+        };
+        let inserted = match inserted {
+            Ok(r) => r,
+            Err(e) => return Json(json!({ "code": 500, "message": format!("保存失败: {}", e) })).into_response(),
+        };
+        // 未开放轮次的灯谜由轮次开放时统一推送
+        if rounds::is_released(&state.db, inserted.round_id, now).await {
+            events::emit(&state.io, inserted.activity_id, events::RIDDLE_PUBLISHED, events::RiddlePublished {
+                activity_id: inserted.activity_id,
+                riddle_ids: vec![inserted.id],
+            });
+        }
+        let mut val = json!(inserted);
+        val["aliases"] = json!(answer::parse_aliases(&inserted.aliases_json));
+        val["tags"] = json!(catalog::parse_tags(&inserted.tags_json));
+        val["hints"] = json!(hints::parse_hints(&inserted.hints_json));
+        if let Some(t) = inserted.add_time {
+            val["add_time"] = json!(format_local(t, state.config.timezone));
+        }
+        Json(json!({ "code": 200, "message": "创建成功", "data": val })).into_response()
+    }
+}
+
+pub async fn delete_riddle(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+) -> impl IntoResponse {
+    sqlx::query("DELETE FROM guess_records WHERE riddle_id = ?").bind(id).execute(&state.db).await.unwrap_or_default();
+    sqlx::query("DELETE FROM hint_reveals WHERE riddle_id = ?").bind(id).execute(&state.db).await.unwrap_or_default();
+    let media_url: Option<String> = sqlx::query_scalar("SELECT media_url FROM riddles WHERE id = ?").bind(id).fetch_optional(&state.db).await.unwrap_or(None);
+    let result = sqlx::query("DELETE FROM riddles WHERE id = ?").bind(id).execute(&state.db).await;
+    match result {
+        Ok(res) if res.rows_affected() > 0 => {
+            media::remove_unused(&state.db, &state.config.template_dir, &media_url.into_iter().collect::<Vec<_>>()).await;
+            Json(json!({ "code": 200, "message": "删除成功" })).into_response()
+        }
+        _ => Json(json!({ "code": 404, "message": "不存在" })).into_response(),
+    }
+}
+
+pub async fn get_leaderboard(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<PaginationParams>,
+) -> impl IntoResponse {
+    let activity_id = scoped_activity(&state, params.activity_id).await;
+    let result = leaderboard_query(activity_id, params.keyword.as_deref(), params.start_date.as_deref(), params.end_date.as_deref(), state.config.timezone)
+        .order_by(params.sort.as_deref(), params.order.as_deref(), &[("solve_time", "gr.solve_time")], "gr.solve_time")
+        .fetch_page::<GuessRecordWithInfo>(&state.db, params.page, params.page_size)
+        .await
+        .map(|page| page.map(|rec| {
+            let mut val = json!(rec);
+            if let Some(t) = rec.solve_time {
+                val["solve_time"] = json!(format_local(t, state.config.timezone));
+            }
+            val
+        }));
+
+    page_response(result)
+}
+
+/// 积分排行榜：按玩家汇总积分与猜中数，可按昵称搜索 (名次不受搜索影响)
+pub async fn get_ranking(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<PaginationParams>,
+) -> impl IntoResponse {
+    let activity_id = scoped_activity(&state, params.activity_id).await;
+    let result = scoring::ranking_query(activity_id, params.keyword.as_deref())
+        .fetch_page::<RankEntry>(&state.db, params.page, params.page_size)
+        .await
+        .map(|page| page.map(|entry| scoring::rank_json(&entry, state.config.timezone)));
+
+    page_response(result)
+}
+
+/// 全部作答记录 (包括答错的)，用于现场核对与补充备选答案
+pub async fn get_records(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<PaginationParams>,
+) -> impl IntoResponse {
+    let activity_id = scoped_activity(&state, params.activity_id).await;
+    let result = attempts_query(activity_id, params.keyword.as_deref(), params.start_date.as_deref(), params.end_date.as_deref(), state.config.timezone)
+        .eq("gr.riddle_id", params.riddle_id.map(Arg::Int))
+        .eq("gr.is_correct", parse_bool_param(params.correct.as_deref()).map(Arg::Bool))
+        .order_by(params.sort.as_deref(), params.order.as_deref(), &[("solve_time", "gr.solve_time"), ("id", "gr.id")], "gr.solve_time")
+        .fetch_page::<GuessRecordWithInfo>(&state.db, params.page, params.page_size)
+        .await
+        .map(|page| page.map(|rec| {
+            let mut val = json!(rec);
+            if let Some(t) = rec.solve_time {
+                val["solve_time"] = json!(format_local(t, state.config.timezone));
+            }
+            val
+        }));
+
+    page_response(result)
+}
+
+#[derive(Deserialize)]
+pub struct ExportParams {
+    pub keyword: Option<String>,
+    pub start_date: Option<String>,
+    pub end_date: Option<String>,
+    pub save_locally: Option<bool>,
+    pub activity_id: Option<i64>,
+    /// 为 true 时导出全部作答记录，否则只导出中奖记录
+    pub attempts: Option<bool>,
+}
+
+pub async fn export_records(
+    State(state): State<Arc<AppState>>,
+    headers: axum::http::HeaderMap,
+    Query(params): Query<ExportParams>,
+) -> impl IntoResponse {
+    // 自动检测环境：如果是从 tauri.localhost 发来的请求，默认开启本地保存
+    let origin = headers.get("origin").and_then(|v| v.to_str().ok()).unwrap_or("");
+    let is_tauri = origin.contains("tauri.localhost") || params.save_locally.unwrap_or(false);
+
+    // 【核心修改】导出接口强制忽略分页，查询全部中奖记录，依然保留按时间倒序
+    let activity_id = scoped_activity(&state, params.activity_id).await;
+    let all_attempts = params.attempts.unwrap_or(false);
+    let query = if all_attempts {
+        attempts_query(activity_id, params.keyword.as_deref(), params.start_date.as_deref(), params.end_date.as_deref(), state.config.timezone)
+    } else {
+        leaderboard_query(activity_id, params.keyword.as_deref(), params.start_date.as_deref(), params.end_date.as_deref(), state.config.timezone)
+    };
+    let items: Vec<GuessRecordWithInfo> = query
+        .order_by(None, None, &[], "gr.solve_time")
+        .fetch_all(&state.db)
+        .await
+        .unwrap_or_default();
+
+    // Generate CSV content with BOM
+    let mut csv = if all_attempts {
+        String::from("\u{feff}记录ID,用户,灯谜,谜底,提交答案,归一化答案,是否答对,是否中奖,作答时间\n")
+    } else {
+        String::from("\u{feff}记录ID,中奖用户,答对灯谜,谜底,提交答案,归一化答案,中奖时间\n")
+    };
+    for item in items {
+        let time_str = item.solve_time.map(|t| format_local(t, state.config.timezone)).unwrap_or_default();
+        let mut fields = vec![
+            item.id.to_string(),
+            csv_field(&item.user_name.unwrap_or_default()),
+            csv_field(&item.riddle_question.unwrap_or_default()),
+            csv_field(&item.riddle_answer.unwrap_or_default()),
+            csv_field(&item.answer.unwrap_or_default()),
+            csv_field(&item.normalized_answer.unwrap_or_default()),
+        ];
+        if all_attempts {
+            fields.push(if item.is_correct { "是" } else { "否" }.to_string());
+            fields.push(if item.is_solved { "是" } else { "否" }.to_string());
+        }
+        fields.push(time_str);
+        csv.push_str(&fields.join(","));
+        csv.push('\n');
+    }
+
+    // --- 桌面端自动保存逻辑 ---
+    if is_tauri {
+        let filename = format!("灯谜中奖记录_{}.csv", to_local(now_utc(), state.config.timezone).format("%Y%m%d_%H%M%S"));
+        
+        // 1. 尝试获取程序同级目录
+        let exe_dir = std::env::current_exe().ok()
+            .and_then(|p| p.parent().map(|p| p.to_path_buf()))
+            .unwrap_or_else(|| std::env::current_dir().unwrap_or_default());
+
+        let save_path = exe_dir.join(&filename);
+
+        // 执行写入
+        match std::fs::write(&save_path, &csv) {
+            Ok(_) => {
+                return Json(json!({
+                    "code": 200,
+                    "message": format!("导出成功！文件已保存至程序所在目录：\n{}", filename),
+                    "data": save_path.to_string_lossy()
+                })).into_response();
+            },
+            Err(e) => {
+                // 如果权限不足，尝试写到用户的桌面（简单兜底）
+                return Json(json!({
+                    "code": 500,
+                    "message": format!("导出失败：文件写入错误 ({})。请尝试以管理员身份运行程序。", e)
+                })).into_response();
+            }
+        }
+    }
+
+    // --- 标准 Web 下载逻辑 ---
+    use axum::http::header;
+    (
+        [
+            (header::CONTENT_TYPE, "text/csv; charset=utf-8"),
+            (header::CONTENT_DISPOSITION, "attachment; filename=\"records.csv\""),
+        ],
+        csv,
+    ).into_response()
+}
+
+/// 活动列表，附带是否为当前活动及灯谜数量
+pub async fn get_activities(
+    State(state): State<Arc<AppState>>,
+) -> impl IntoResponse {
+    let active_id = activity::active_id(&state.db).await;
+    let activities: Vec<Activity> = sqlx::query_as("SELECT * FROM activities ORDER BY id DESC")
+        .fetch_all(&state.db)
+        .await
+        .unwrap_or_default();
+    let counts: Vec<(i64, i64)> = sqlx::query_as("SELECT activity_id, COUNT(*) FROM riddles WHERE activity_id IS NOT NULL GROUP BY activity_id")
+        .fetch_all(&state.db)
+        .await
+        .unwrap_or_default();
+
+    let list: Vec<serde_json::Value> = activities.into_iter().map(|a| {
+        let riddle_count = counts.iter().find(|(id, _)| *id == a.id).map(|(_, c)| *c).unwrap_or(0);
+        let mut val = activity_json(&a, state.config.timezone);
+        val["is_active"] = json!(Some(a.id) == active_id);
+        val["riddle_count"] = json!(riddle_count);
+        val
+    }).collect();
+
+    Json(json!({ "code": 200, "message": "success", "data": list }))
+}
+
+#[derive(Deserialize)]
+pub struct ActivityPayload {
+    /// 为空时新建活动
+    pub id: Option<i64>,
+    pub name: Option<String>,
+    pub start_time: String,
+    pub end_time: String,
+    /// 每位玩家每道灯谜最多作答次数，0 表示不限；修改时不传则保持不变
+    pub max_attempts: Option<i64>,
+    /// 两次作答之间的最短间隔 (秒)
+    pub cooldown_secs: Option<i64>,
+    /// 各难度的积分系数，如 `[1, 1.5, 2]`
+    pub difficulty_multipliers: Option<Vec<f64>>,
+    /// 抢答加分上限，0 表示不加分
+    pub speed_bonus: Option<i64>,
+    /// 抢答加分的时间窗口 (秒)
+    pub speed_bonus_secs: Option<i64>,
+}
+
+async fn save_activity_row(state: &AppState, id: Option<i64>, payload: ActivityPayload) -> Result<Activity, sqlx::Error> {
+    // 管理员按活动时区填写起止时间
+    let tz = state.config.timezone;
+    let parse = |value: &str| NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S").map(|t| to_utc(t, tz)).unwrap_or_else(|_| now_utc());
+    let start = parse(&payload.start_time);
+    let end = parse(&payload.end_time);
+    let name = payload.name.unwrap_or_else(|| "元宵猜灯谜".to_string());
+    let max_attempts = payload.max_attempts.map(|n| n.max(0));
+    let cooldown_secs = payload.cooldown_secs.map(|n| n.max(0));
+    let multipliers_json = payload.difficulty_multipliers.map(|m| serde_json::to_string(&scoring::clean_multipliers(m)).unwrap());
+    let speed_bonus = payload.speed_bonus.map(|n| n.max(0));
+    let speed_bonus_secs = payload.speed_bonus_secs.map(|n| n.max(0));
+
+    let id = match id {
+        Some(id) => {
+            sqlx::query(
+                "UPDATE activities SET name = ?, start_time = ?, end_time = ?, max_attempts = COALESCE(?, max_attempts), cooldown_secs = COALESCE(?, cooldown_secs),
+                 difficulty_multipliers_json = COALESCE(?, difficulty_multipliers_json), speed_bonus = COALESCE(?, speed_bonus), speed_bonus_secs = COALESCE(?, speed_bonus_secs) WHERE id = ?"
+            )
+                .bind(&name).bind(start).bind(end).bind(max_attempts).bind(cooldown_secs)
+                .bind(multipliers_json).bind(speed_bonus).bind(speed_bonus_secs).bind(id)
+                .execute(&state.db).await?;
+            id
+        }
+        None => sqlx::query(
+                "INSERT INTO activities (name, start_time, end_time, max_attempts, cooldown_secs, difficulty_multipliers_json, speed_bonus, speed_bonus_secs)
+                 VALUES (?, ?, ?, ?, ?, COALESCE(?, '[]'), COALESCE(?, 0), COALESCE(?, 300))"
+            )
+            .bind(&name).bind(start).bind(end)
+            .bind(max_attempts.unwrap_or(activity::DEFAULT_MAX_ATTEMPTS)).bind(cooldown_secs.unwrap_or(0))
+            .bind(multipliers_json).bind(speed_bonus).bind(speed_bonus_secs)
+            .execute(&state.db).await?
+            .last_insert_rowid(),
+    };
+    sqlx::query_as("SELECT * FROM activities WHERE id = ?").bind(id).fetch_one(&state.db).await
+}
+
+/// 新建或修改活动
+pub async fn save_activity(
+    State(state): State<Arc<AppState>>,
+    Json(payload): Json<ActivityPayload>,
+) -> impl IntoResponse {
+    if let Some(id) = payload.id {
+        if activity::find(&state.db, id).await.is_none() {
+            return Json(json!({ "code": 404, "message": "活动不存在" })).into_response();
+        }
+    }
+    match save_activity_row(&state, payload.id, payload).await {
+        Ok(act) => Json(json!({ "code": 200, "message": "保存成功", "data": activity_json(&act, state.config.timezone) })).into_response(),
+        Err(e) => Json(json!({ "code": 500, "message": format!("保存失败: {}", e) })).into_response(),
+    }
+}
+
+/// 设为当前活动：未指定活动的大屏、玩家页面和管理列表都使用当前活动
+pub async fn activate_activity(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+) -> impl IntoResponse {
+    let act = match activity::find(&state.db, id).await {
+        Some(a) => a,
+        None => return Json(json!({ "code": 404, "message": "活动不存在" })).into_response(),
+    };
+    match activity::set_active(&state.db, id).await {
+        Ok(_) => Json(json!({ "code": 200, "message": "已切换当前活动", "data": activity_json(&act, state.config.timezone) })).into_response(),
+        Err(e) => Json(json!({ "code": 500, "message": format!("切换失败: {}", e) })).into_response(),
+    }
+}
+
+#[derive(Deserialize)]
+pub struct ExtendPayload {
+    /// 延长的分钟数，默认 10 分钟
+    pub minutes: Option<i64>,
+}
+
+/// 暂停、恢复、延长、开始、结束活动；状态变化由后台任务推送给大屏
+async fn control_activity(state: &AppState, id: i64, control: activity::Control, done: &str) -> axum::response::Response {
+    let act = match activity::find(&state.db, id).await {
+        Some(a) => a,
+        None => return Json(json!({ "code": 404, "message": "活动不存在" })).into_response(),
+    };
+    match activity::control(&state.db, &act, control, now_utc()).await {
+        Ok(updated) => Json(json!({ "code": 200, "message": done, "data": activity_json(&updated, state.config.timezone) })).into_response(),
+        Err(msg) => Json(json!({ "code": 400, "message": msg })).into_response(),
+    }
+}
+
+pub async fn pause_activity(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+) -> impl IntoResponse {
+    control_activity(&state, id, activity::Control::Pause, "活动已暂停").await
+}
+
+pub async fn resume_activity(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+) -> impl IntoResponse {
+    control_activity(&state, id, activity::Control::Resume, "活动已恢复").await
+}
+
+pub async fn extend_activity(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+    payload: Option<Json<ExtendPayload>>,
+) -> impl IntoResponse {
+    let minutes = payload.and_then(|Json(p)| p.minutes).unwrap_or(10);
+    control_activity(&state, id, activity::Control::Extend(minutes), &format!("活动已延长 {} 分钟", minutes)).await
+}
+
+pub async fn open_activity(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+) -> impl IntoResponse {
+    control_activity(&state, id, activity::Control::Open, "活动已开始").await
+}
+
+pub async fn close_activity(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+) -> impl IntoResponse {
+    control_activity(&state, id, activity::Control::Close, "活动已结束").await
+}
+
+/// 删除活动及其下的灯谜和答题记录
+pub async fn delete_activity(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+) -> impl IntoResponse {
+    let media_urls: Vec<String> = sqlx::query_scalar("SELECT DISTINCT media_url FROM riddles WHERE activity_id = ? AND media_url != ''")
+        .bind(id).fetch_all(&state.db).await.unwrap_or_default();
+    let result: Result<u64, sqlx::Error> = async {
+        let mut tx = state.db.begin().await?;
+        sqlx::query("DELETE FROM guess_records WHERE riddle_id IN (SELECT id FROM riddles WHERE activity_id = ?)")
+            .bind(id).execute(&mut *tx).await?;
+        sqlx::query("DELETE FROM hint_reveals WHERE riddle_id IN (SELECT id FROM riddles WHERE activity_id = ?)")
+            .bind(id).execute(&mut *tx).await?;
+        sqlx::query("DELETE FROM riddles WHERE activity_id = ?").bind(id).execute(&mut *tx).await?;
+        sqlx::query("DELETE FROM rounds WHERE activity_id = ?").bind(id).execute(&mut *tx).await?;
+        let res = sqlx::query("DELETE FROM activities WHERE id = ?").bind(id).execute(&mut *tx).await?;
+        tx.commit().await?;
+        Ok(res.rows_affected())
+    }.await;
+
+    match result {
+        Ok(n) if n > 0 => {
+            media::remove_unused(&state.db, &state.config.template_dir, &media_urls).await;
+            Json(json!({ "code": 200, "message": "删除成功" })).into_response()
+        }
+        Ok(_) => Json(json!({ "code": 404, "message": "活动不存在" })).into_response(),
+        Err(e) => Json(json!({ "code": 500, "message": format!("删除失败: {}", e) })).into_response(),
+    }
+}
+
+/// 轮次的 JSON 表示，时间按活动时区显示
+fn round_json(round: &Round, riddle_count: i64, tz: FixedOffset) -> serde_json::Value {
+    let mut val = json!(round);
+    val["release_time"] = json!(format_local(round.release_time, tz));
+    val["announced_at"] = json!(round.announced_at.map(|t| format_local(t, tz)));
+    val["released"] = json!(round.release_time <= now_utc());
+    val["riddle_count"] = json!(riddle_count);
+    val
+}
+
+/// 活动的轮次列表，附带灯谜数量
+pub async fn get_rounds(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<PaginationParams>,
+) -> impl IntoResponse {
+    let activity_id = match scoped_activity(&state, params.activity_id).await {
+        Some(id) => id,
+        None => return Json(json!({ "code": 200, "message": "success", "data": [] })).into_response(),
+    };
+    let counts: Vec<(i64, i64)> = sqlx::query_as("SELECT round_id, COUNT(*) FROM riddles WHERE round_id IS NOT NULL AND activity_id = ? GROUP BY round_id")
+        .bind(activity_id)
+        .fetch_all(&state.db)
+        .await
+        .unwrap_or_default();
+    let list: Vec<serde_json::Value> = rounds::list(&state.db, activity_id).await.iter().map(|r| {
+        let riddle_count = counts.iter().find(|(id, _)| *id == r.id).map(|(_, c)| *c).unwrap_or(0);
+        round_json(r, riddle_count, state.config.timezone)
+    }).collect();
+    Json(json!({ "code": 200, "message": "success", "data": list })).into_response()
+}
+
+#[derive(Deserialize)]
+pub struct RoundPayload {
+    /// 为空时新建轮次
+    pub id: Option<i64>,
+    /// 所属活动，新建时默认为当前活动；修改时不能变更
+    pub activity_id: Option<i64>,
+    pub name: Option<String>,
+    /// 发布时间，按活动时区填写 `YYYY-MM-DD HH:MM:SS`
+    pub release_time: String,
+}
+
+/// 新建或修改轮次；发布时间改到将来时，开放后会重新推送
+pub async fn save_round(
+    State(state): State<Arc<AppState>>,
+    Json(payload): Json<RoundPayload>,
+) -> impl IntoResponse {
+    let release_time = match NaiveDateTime::parse_from_str(&payload.release_time, "%Y-%m-%d %H:%M:%S") {
+        Ok(t) => to_utc(t, state.config.timezone),
+        Err(_) => return Json(json!({ "code": 400, "message": "发布时间格式错误" })).into_response(),
+    };
+    let name = payload.name.map(|n| n.trim().to_string()).filter(|n| !n.is_empty());
+
+    let result = match payload.id {
+        Some(id) => {
+            let round = match rounds::find(&state.db, id).await {
+                Some(r) => r,
+                None => return Json(json!({ "code": 404, "message": "轮次不存在" })).into_response(),
+            };
+            sqlx::query("UPDATE rounds SET name = ?, release_time = ?, announced_at = CASE WHEN ? > ? THEN NULL ELSE announced_at END WHERE id = ?")
+                .bind(name.unwrap_or(round.name)).bind(release_time).bind(release_time).bind(now_utc()).bind(id)
+                .execute(&state.db).await
+                .map(|_| id)
+        }
+        None => {
+            let activity_id = match scoped_activity(&state, payload.activity_id).await {
+                Some(id) if activity::find(&state.db, id).await.is_some() => id,
+                _ => return Json(json!({ "code": 404, "message": "活动不存在" })).into_response(),
+            };
+            let index = rounds::list(&state.db, activity_id).await.len() + 1;
+            sqlx::query("INSERT INTO rounds (activity_id, name, release_time) VALUES (?, ?, ?)")
+                .bind(activity_id).bind(name.unwrap_or_else(|| format!("第 {} 轮", index))).bind(release_time)
+                .execute(&state.db).await
+                .map(|res| res.last_insert_rowid())
+        }
+    };
+
+    match result {
+        Ok(id) => {
+            let round = rounds::find(&state.db, id).await.unwrap();
+            let riddle_count = rounds::riddle_count(&state.db, id).await;
+            Json(json!({ "code": 200, "message": "保存成功", "data": round_json(&round, riddle_count, state.config.timezone) })).into_response()
+        }
+        Err(e) => Json(json!({ "code": 500, "message": format!("保存失败: {}", e) })).into_response(),
+    }
+}
+
+/// 立即开放轮次：发布时间改为现在，由后台任务推送
+pub async fn open_round(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+) -> impl IntoResponse {
+    let round = match rounds::find(&state.db, id).await {
+        Some(r) => r,
+        None => return Json(json!({ "code": 404, "message": "轮次不存在" })).into_response(),
+    };
+    let now = now_utc();
+    if round.release_time <= now {
+        return Json(json!({ "code": 400, "message": "轮次已经开放" })).into_response();
+    }
+    let now = now.with_nanosecond(0).unwrap_or(now);
+    sqlx::query("UPDATE rounds SET release_time = ? WHERE id = ?").bind(now).bind(id).execute(&state.db).await.unwrap_or_default();
+    let round = rounds::find(&state.db, id).await.unwrap_or(round);
+    let riddle_count = rounds::riddle_count(&state.db, id).await;
+    Json(json!({ "code": 200, "message": "轮次已开放", "data": round_json(&round, riddle_count, state.config.timezone) })).into_response()
+}
+
+/// 删除轮次，其中的灯谜改为不分轮次并立即开放
+pub async fn delete_round(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+) -> impl IntoResponse {
+    let result: Result<u64, sqlx::Error> = async {
+        let mut tx = state.db.begin().await?;
+        sqlx::query("UPDATE riddles SET round_id = NULL WHERE round_id = ?").bind(id).execute(&mut *tx).await?;
+        let res = sqlx::query("DELETE FROM rounds WHERE id = ?").bind(id).execute(&mut *tx).await?;
+        tx.commit().await?;
+        Ok(res.rows_affected())
+    }.await;
+
+    match result {
+        Ok(n) if n > 0 => Json(json!({ "code": 200, "message": "删除成功" })).into_response(),
+        Ok(_) => Json(json!({ "code": 404, "message": "轮次不存在" })).into_response(),
+        Err(e) => Json(json!({ "code": 500, "message": format!("删除失败: {}", e) })).into_response(),
+    }
+}
+
+/// 当前活动的配置，没有任何活动时创建一个默认活动
+pub async fn get_activity(
+    State(state): State<Arc<AppState>>,
+) -> impl IntoResponse {
+    if let Some(a) = activity::active(&state.db).await {
+        return Json(activity_json(&a, state.config.timezone)).into_response();
+    }
+    
+    let now = now_utc();
+    let tomorrow = now + chrono::Duration::days(1);
+    let res = sqlx::query("INSERT INTO activities (name, start_time, end_time) VALUES (?, ?, ?)")
+        .bind("元宵猜灯谜").bind(now).bind(tomorrow).execute(&state.db).await.unwrap();
+    
+    let act: Activity = sqlx::query_as("SELECT * FROM activities WHERE id = ?").bind(res.last_insert_rowid()).fetch_one(&state.db).await.unwrap();
+    Json(activity_json(&act, state.config.timezone)).into_response()
+}
+
+/// 修改当前活动的配置，没有任何活动时新建
+pub async fn update_activity(
+    State(state): State<Arc<AppState>>,
+    Json(payload): Json<ActivityPayload>,
+) -> impl IntoResponse {
+    let active_id = activity::active_id(&state.db).await;
+    match save_activity_row(&state, active_id, payload).await {
+        Ok(updated) => Json(activity_json(&updated, state.config.timezone)).into_response(),
+        Err(e) => Json(json!({ "code": 500, "message": format!("保存失败: {}", e) })).into_response(),
+    }
+}
+
+/// 读出上传的表格；zip 包中的表格与媒体文件一起返回
+fn read_upload(filename: String, data: &[u8]) -> Result<(Option<media::Bundle>, sheet::Table), String> {
+    // 支持上传 zip 包：表格中的媒体列填写包内的图片或音频文件名
+    let bundle = media::unpack(data)?;
+    // xlsx、xls、ods、csv 与 json 都读成表头加数据行，共用同一套列识别
+    let (filename, data) = match &bundle {
+        Some(b) => (b.sheet_name.clone(), b.sheet.clone()),
+        None => (filename, data.to_vec()),
+    };
+    match sheet::read(&filename, data)? {
+        t if t.headers.is_empty() => Err("表格为空".to_string()),
+        t => Ok((bundle, t)),
+    }
+}
+
+/// 可以映射到的列，供后台的列映射下拉框使用
+fn column_fields() -> Vec<serde_json::Value> {
+    importer::Column::ALL.iter().map(|c| json!({ "value": c, "label": c.label() })).collect()
+}
+
+pub async fn import_riddles(
+    State(state): State<Arc<AppState>>,
+    mut multipart: Multipart,
+) -> impl IntoResponse {
+    let mut file_data = None;
+    let mut filename = String::new();
+    let mut activity_id = None;
+    let mut dry_run = false;
+    let mut mapping_name = None;
+    let mut mapping = importer::Mapping::new();
+    // 导入的灯谜默认按玩家打乱选项，未指定种子时随机生成
+    let mut opts = importer::ImportOptions { option_order: OptionOrder::Shuffle, option_seed: rand::random::<u32>() as i64, ..Default::default() };
+    while let Ok(Some(field)) = multipart.next_field().await {
+        match field.name() {
+            Some("file") => {
+                filename = field.file_name().unwrap_or_default().to_string();
+                file_data = field.bytes().await.ok();
+            }
+            Some("activity_id") => activity_id = field.text().await.ok().and_then(|v| v.trim().parse::<i64>().ok()),
+            Some("dry_run") => dry_run = field.text().await.is_ok_and(|v| matches!(v.trim(), "1" | "true")),
+            Some("mode") => match field.text().await.ok().and_then(|v| serde_json::from_value(json!(v.trim())).ok()) {
+                Some(m) => opts.mode = m,
+                None => return Json(json!({ "code": 400, "message": "mode 只能是 skip、append 或 upsert" })).into_response(),
+            },
+            Some("key") => match field.text().await.ok().and_then(|v| serde_json::from_value(json!(v.trim())).ok()) {
+                Some(k) => opts.key = k,
+                None => return Json(json!({ "code": 400, "message": "key 只能是 question 或 external_id" })).into_response(),
+            },
+            Some("option_order") => match field.text().await.ok().and_then(|v| serde_json::from_value(json!(v.trim())).ok()) {
+                Some(o) => opts.option_order = o,
+                None => return Json(json!({ "code": 400, "message": "option_order 只能是 keep 或 shuffle" })).into_response(),
+            },
+            Some("seed") => match field.text().await.ok().and_then(|v| v.trim().parse::<i64>().ok()) {
+                Some(seed) => opts.option_seed = seed,
+                None => return Json(json!({ "code": 400, "message": "seed 必须是整数" })).into_response(),
+            },
+            Some("mapping") => match field.text().await.ok().and_then(|v| serde_json::from_str(&v).ok()) {
+                Some(m) => mapping = m,
+                None => return Json(json!({ "code": 400, "message": "mapping 格式错误，需要表头到列名的 JSON 对象" })).into_response(),
+            },
+            Some("mapping_name") => mapping_name = field.text().await.ok().map(|v| v.trim().to_string()).filter(|v| !v.is_empty()),
+            _ => {}
+        }
+    }
+    // 使用保存的列映射，同时上传的 mapping 中的表头优先
+    if let Some(name) = mapping_name {
+        match mappings::find_by_name(&state.db, &name).await {
+            Some(saved) => {
+                let mut merged = mappings::mapping(&saved);
+                merged.append(&mut mapping);
+                mapping = merged;
+            }
+            None => return Json(json!({ "code": 404, "message": format!("列映射 {} 不存在", name) })).into_response(),
+        }
+    }
+    // 导入到指定活动，未指定时导入到当前活动
+    let activity_id = scoped_activity(&state, activity_id).await;
+    opts.activity_id = activity_id;
+
+    let Some(data) = file_data else {
+        return Json(json!({ "code": 400, "message": "未上传文件" })).into_response();
+    };
+    let (bundle, table) = match read_upload(filename, &data) {
+        Ok(upload) => upload,
+        Err(msg) => return Json(json!({ "code": 400, "message": msg })).into_response(),
+    };
+    let columns = importer::columns(&table.headers, &mapping);
+    if !columns.contains(&Some(importer::Column::Question)) || !columns.contains(&Some(importer::Column::Answer)) {
+        return Json(json!({
+            "code": 400,
+            "message": format!("没有找到灯谜题目或正确答案列，请设置列映射 (表头: {})", table.headers.join("、")),
+        })).into_response();
+    }
+
+    let mut rows = importer::parse_rows(&columns, table.rows);
+    if let Err(e) = importer::plan(&state.db, &mut rows, &opts, bundle.as_ref()).await {
+        return Json(json!({ "code": 500, "message": format!("导入失败: {}", e) })).into_response();
+    }
+    let count = |status| rows.iter().filter(|r| r.report.status == status).count();
+    let (inserted, updated, skipped, failed) = (
+        count(importer::RowStatus::Insert),
+        count(importer::RowStatus::Update),
+        count(importer::RowStatus::Skip),
+        count(importer::RowStatus::Error),
+    );
+
+    // 预检或有错误的行时不写入任何数据，只返回逐行报告
+    let applied = if dry_run || failed > 0 {
+        false
+    } else {
+        let now = now_utc();
+        let date_dir = to_local(now, state.config.timezone).format("%Y/%m/%d").to_string();
+        match importer::apply(&state.db, &mut rows, &opts, bundle.as_ref(), &state.config.template_dir, &date_dir, now).await {
+            Ok(result) => {
+                media::remove_unused(&state.db, &state.config.template_dir, &result.replaced_media).await;
+                if !result.inserted_ids.is_empty() {
+                    events::emit(&state.io, activity_id, events::RIDDLE_PUBLISHED, events::RiddlePublished { activity_id, riddle_ids: result.inserted_ids });
+                }
+                true
+            }
+            Err(e) => return Json(json!({ "code": 500, "message": format!("导入失败，已全部回滚: {}", e) })).into_response(),
+        }
+    };
+
+    let message = if applied {
+        format!("灯谜导入成功: 新增 {} 条，更新 {} 条，跳过 {} 条", inserted, updated, skipped)
+    } else if failed > 0 {
+        format!("有 {} 行存在错误，未导入任何灯谜", failed)
+    } else {
+        format!("预检通过: 将新增 {} 条，更新 {} 条，跳过 {} 条", inserted, updated, skipped)
+    };
+    let reports: Vec<&importer::RowReport> = rows.iter().map(|r| &r.report).collect();
+    Json(json!({
+        "code": 200,
+        "message": message,
+        "data": {
+            "applied": applied,
+            "inserted": inserted,
+            "updated": updated,
+            "skipped": skipped,
+            "failed": failed,
+            "option_order": opts.option_order,
+            "seed": opts.option_seed,
+            "columns": table.headers.iter().zip(&columns).map(|(h, c)| json!({ "header": h, "column": c })).collect::<Vec<_>>(),
+            "rows": reports,
+        }
+    })).into_response()
+}
+
+/// 下载导入模板，表头、示例与填写说明都按导入时的列定义生成
+pub async fn download_import_template() -> impl IntoResponse {
+    use axum::http::header;
+    match sheet::write_xlsx(&importer::template()) {
+        Ok(data) => (
+            [
+                (header::CONTENT_TYPE, "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet"),
+                (header::CONTENT_DISPOSITION, "attachment; filename=\"riddle_template.xlsx\""),
+            ],
+            data,
+        ).into_response(),
+        Err(e) => Json(json!({ "code": 500, "message": format!("生成模板失败: {}", e) })).into_response(),
+    }
+}
+
+/// 读取上传表格的表头，给出建议的列映射：优先使用与表头最吻合的已保存映射，其余表头按名称识别
+pub async fn suggest_import_mapping(
+    State(state): State<Arc<AppState>>,
+    mut multipart: Multipart,
+) -> impl IntoResponse {
+    let mut file_data = None;
+    let mut filename = String::new();
+    while let Ok(Some(field)) = multipart.next_field().await {
+        if field.name() == Some("file") {
+            filename = field.file_name().unwrap_or_default().to_string();
+            file_data = field.bytes().await.ok();
+        }
+    }
+    let Some(data) = file_data else {
+        return Json(json!({ "code": 400, "message": "未上传文件" })).into_response();
+    };
+    let (_, table) = match read_upload(filename, &data) {
+        Ok(upload) => upload,
+        Err(msg) => return Json(json!({ "code": 400, "message": msg })).into_response(),
+    };
+
+    let saved = mappings::list(&state.db).await;
+    let matched = mappings::best_match(&saved, &table.headers);
+    let mut mapping = importer::suggest(&table.headers);
+    if let Some(m) = matched {
+        let saved_mapping = mappings::mapping(m);
+        for (header, column) in mapping.iter_mut() {
+            if let Some(c) = saved_mapping.get(header) {
+                *column = *c;
+            }
+        }
+    }
+    Json(json!({
+        "code": 200,
+        "message": "success",
+        "data": {
+            "headers": table.headers,
+            // 前几行数据，方便确认每列的内容
+            "samples": table.rows.iter().take(3).collect::<Vec<_>>(),
+            "mapping": mapping,
+            "mapping_name": matched.map(|m| m.name.clone()),
+            "fields": column_fields(),
+        }
+    })).into_response()
+}
+
+/// 已保存的列映射
+pub async fn get_import_mappings(State(state): State<Arc<AppState>>) -> impl IntoResponse {
+    let list: Vec<serde_json::Value> = mappings::list(&state.db).await.iter().map(|m| json!({
+        "id": m.id,
+        "name": m.name,
+        "mapping": mappings::mapping(m),
+        "updated_at": format_local(m.updated_at, state.config.timezone),
+    })).collect();
+    Json(json!({ "code": 200, "message": "success", "data": { "list": list, "fields": column_fields() } })).into_response()
+}
+
+#[derive(Deserialize)]
+pub struct ImportMappingPayload {
+    pub name: String,
+    /// 表头到列名的映射，列名为 `null` 表示忽略该列
+    pub mapping: importer::Mapping,
+}
+
+/// 按名称保存列映射，同名的覆盖
+pub async fn save_import_mapping(
+    State(state): State<Arc<AppState>>,
+    Json(payload): Json<ImportMappingPayload>,
+) -> impl IntoResponse {
+    let name = payload.name.trim();
+    if name.is_empty() {
+        return Json(json!({ "code": 400, "message": "映射名称不能为空" })).into_response();
+    }
+    let mapping: importer::Mapping = payload.mapping.into_iter().map(|(h, c)| (h.trim().to_string(), c)).filter(|(h, _)| !h.is_empty()).collect();
+    match mappings::save(&state.db, name, &mapping, now_utc()).await {
+        Ok(id) => Json(json!({ "code": 200, "message": "保存成功", "data": { "id": id } })).into_response(),
+        Err(e) => Json(json!({ "code": 500, "message": format!("保存失败: {}", e) })).into_response(),
+    }
+}
+
+pub async fn delete_import_mapping(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+) -> impl IntoResponse {
+    if mappings::delete(&state.db, id).await {
+        Json(json!({ "code": 200, "message": "删除成功" })).into_response()
+    } else {
+        Json(json!({ "code": 404, "message": "列映射不存在" })).into_response()
+    }
+}
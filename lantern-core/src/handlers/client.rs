@@ -0,0 +1,778 @@
+use axum::{
+    extract::{Path, Query, State, Multipart},
+    http::StatusCode,
+    response::{Html, IntoResponse, Json},
+};
+use axum_extra::extract::cookie::CookieJar;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use sqlx::SqlitePool;
+use crate::{AppState, activity, answer, auth, catalog, events, hints, options, rounds, scoring, models::*, ax_extract::MaybeFormOrJson, utils::{format_local, get_local_ip, now_utc, to_local}};
+use chrono::NaiveDateTime;
+use serde_json::json;
+use uuid::Uuid;
+use tokio::fs;
+
+#[derive(Deserialize)]
+pub struct RiddleParams {
+    pub page: Option<u32>,
+    #[serde(rename = "pageSize")]
+    pub page_size: Option<u32>,
+    pub exclude_ids: Option<String>,
+    /// 所属活动，未指定时为当前活动
+    pub activity_id: Option<i64>,
+    /// 只出指定分类、标签或难度的灯谜
+    pub category: Option<String>,
+    pub tag: Option<String>,
+    pub difficulty: Option<i64>,
+}
+
+/// 大屏与玩家页面的活动参数，如 `/frontend/index?activity_id=2`，未指定时为当前活动
+#[derive(Deserialize)]
+pub struct ActivityParams {
+    pub activity_id: Option<i64>,
+}
+
+pub async fn get_lantern(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<ActivityParams>,
+) -> impl IntoResponse {
+    let activity = activity::resolve(&state.db, params.activity_id).await;
+
+    let mut ctx = tera::Context::new();
+    ctx.insert("activity", &activity);
+    if let Some(ip) = get_local_ip() {
+        ctx.insert("local_ip", &ip);
+    }
+    
+    match state.tera.render("index.html", &ctx) {
+        Ok(html) => Html(html).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, format!("Template error: {}", e)).into_response(),
+    }
+}
+
+pub async fn get_frontend_index(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<ActivityParams>,
+) -> impl IntoResponse {
+    let activity = activity::resolve(&state.db, params.activity_id).await;
+    let activity_state = activity.as_ref().map(|a| activity::state_at(a, now_utc()));
+
+    let mut ctx = tera::Context::new();
+    ctx.insert("activity", &activity);
+    ctx.insert("activity_state", &activity_state);
+    if let Some(ip) = get_local_ip() {
+        ctx.insert("local_ip", &ip);
+    }
+    
+    match state.tera.render("frontend/index.html", &ctx) {
+        Ok(html) => Html(html).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, format!("Template error: {}", e)).into_response(),
+    }
+}
+
+/// 大屏与公开接口返回的灯谜：不含提示原文、备选答案与打乱选项的种子，提示只返回已自动揭晓的，谜底在灯谜被猜中后才公开
+#[derive(Serialize)]
+pub struct PublicRiddle {
+    pub id: i64,
+    pub activity_id: Option<i64>,
+    pub question: String,
+    pub remark: Option<String>,
+    pub options: Vec<String>,
+    pub answer: Option<String>,
+    pub match_mode: MatchMode,
+    pub points: i64,
+    pub difficulty: i64,
+    pub shown_at: Option<NaiveDateTime>,
+    pub add_time: Option<NaiveDateTime>,
+    pub is_solved: bool,
+    pub solver_id: Option<i64>,
+    pub round_id: Option<i64>,
+    pub category: String,
+    pub tags_json: String,
+    pub tags: Vec<String>,
+    pub hints: Vec<hints::HintView>,
+    pub media_type: MediaType,
+    pub media_url: String,
+    pub option_order: OptionOrder,
+    pub solver_name: Option<String>,
+    pub solver_avatar: Option<String>,
+}
+
+fn public_riddle(state: &AppState, r: RiddleWithSolver) -> PublicRiddle {
+    // 没有玩家身份，只有到时间自动揭晓的提示才返回内容
+    let hints = hints::views(&hints::parse_hints(&r.hints_json), r.shown_at, &[], now_utc(), state.config.timezone);
+    PublicRiddle {
+        options: serde_json::from_str(&r.options_json).unwrap_or_default(),
+        tags: catalog::parse_tags(&r.tags_json),
+        hints,
+        id: r.id,
+        activity_id: r.activity_id,
+        question: r.question,
+        remark: r.remark,
+        answer: r.is_solved.then_some(r.answer),
+        match_mode: r.match_mode,
+        points: r.points,
+        difficulty: r.difficulty,
+        shown_at: r.shown_at,
+        add_time: r.add_time,
+        is_solved: r.is_solved,
+        solver_id: r.solver_id,
+        round_id: r.round_id,
+        category: r.category,
+        tags_json: r.tags_json,
+        media_type: r.media_type,
+        media_url: r.media_url,
+        option_order: r.option_order,
+        solver_name: r.solver_name,
+        solver_avatar: r.solver_avatar,
+    }
+}
+
+pub async fn get_riddles(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<RiddleParams>,
+) -> impl IntoResponse {
+    let page = params.page.unwrap_or(1);
+    let page_size = params.page_size.unwrap_or(1);
+    let offset = (page - 1) * page_size;
+    
+    let exclude_ids: Vec<i64> = params.exclude_ids
+        .unwrap_or_default()
+        .split(',')
+        .filter_map(|s| s.parse().ok())
+        .collect();
+
+    let activity_id = match params.activity_id {
+        Some(id) => Some(id),
+        None => activity::active_id(&state.db).await,
+    };
+
+    let mut query_str = String::from(
+        "SELECT r.*, u.username as solver_name, u.avatar as solver_avatar 
+         FROM riddles r 
+         LEFT JOIN users u ON r.solver_id = u.id 
+         WHERE r.is_solved = 0 AND r.activity_id = ?"
+    );
+    // 分轮次的灯谜到发布时间后才出现在大屏上
+    query_str.push_str(&format!(" AND {}", rounds::RELEASED));
+
+    let category = params.category.as_deref().map(str::trim).filter(|c| !c.is_empty());
+    let tag = params.tag.as_deref().map(str::trim).filter(|t| !t.is_empty());
+    if category.is_some() {
+        query_str.push_str(" AND r.category = ?");
+    }
+    if tag.is_some() {
+        query_str.push_str(&format!(" AND {}", catalog::HAS_TAG));
+    }
+    if params.difficulty.is_some() {
+        query_str.push_str(" AND r.difficulty = ?");
+    }
+
+    if !exclude_ids.is_empty() {
+        let placeholders: Vec<String> = exclude_ids.iter().map(|_| "?".to_string()).collect();
+        query_str.push_str(&format!(" AND r.id NOT IN ({})", placeholders.join(",")));
+    }
+
+    query_str.push_str(" LIMIT ? OFFSET ?");
+
+    let mut query = sqlx::query_as::<_, RiddleWithSolver>(&query_str).bind(activity_id).bind(now_utc());
+    if let Some(c) = category {
+        query = query.bind(c);
+    }
+    if let Some(t) = tag {
+        query = query.bind(t);
+    }
+    if let Some(d) = params.difficulty {
+        query = query.bind(d);
+    }
+    
+    for id in &exclude_ids {
+        query = query.bind(id);
+    }
+    query = query.bind(page_size).bind(offset);
+
+    let items = query.fetch_all(&state.db).await.unwrap_or_default();
+
+    if items.is_empty() {
+        return Json(json!({ "code": 404, "message": "没有数据" })).into_response();
+    }
+
+    // 出现在大屏上即开始计算抢答加分
+    let ids: Vec<i64> = items.iter().map(|r| r.id).collect();
+    scoring::mark_shown(&state.db, &ids, now_utc()).await;
+
+    let result: Vec<PublicRiddle> = items.into_iter().map(|item| public_riddle(&state, item)).collect();
+
+    Json(json!({
+        "code": 200,
+        "message": "success",
+        "data": result
+    })).into_response()
+}
+
+pub async fn get_riddle_by_id(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+) -> impl IntoResponse {
+    let sql = format!(
+        "SELECT r.*, u.username as solver_name, u.avatar as solver_avatar 
+         FROM riddles r 
+         LEFT JOIN users u ON r.solver_id = u.id 
+         WHERE r.id = ? AND {}",
+        rounds::RELEASED
+    );
+    let riddle: Option<RiddleWithSolver> = sqlx::query_as(&sql)
+    .bind(id)
+    .bind(now_utc())
+    .fetch_optional(&state.db)
+    .await
+    .unwrap_or(None);
+
+    if let Some(r) = riddle {
+        return Json(json!({ "data": public_riddle(&state, r), "code": 200 })).into_response();
+    }
+    Json(json!({ "code": 400 })).into_response()
+}
+
+/// 大屏可选的分类、标签与难度：只统计已开放且未被猜中的灯谜
+pub async fn get_riddle_facets(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<ActivityParams>,
+) -> impl IntoResponse {
+    let activity_id = match params.activity_id {
+        Some(id) => Some(id),
+        None => activity::active_id(&state.db).await,
+    };
+    Json(json!({ "code": 200, "message": "success", "data": catalog::facets(&state.db, activity_id, Some(now_utc())).await }))
+}
+
+pub async fn get_q(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<std::collections::HashMap<String, String>>,
+    jar: CookieJar,
+) -> impl IntoResponse {
+    let riddle_id = params.get("r_id").and_then(|id| id.parse::<i64>().ok());
+    let riddle: Option<Riddle> = match riddle_id {
+        Some(rid) => sqlx::query_as(&format!("SELECT * FROM riddles r WHERE r.id = ? AND {}", rounds::RELEASED))
+            .bind(rid)
+            .bind(now_utc())
+            .fetch_optional(&state.db)
+            .await
+            .unwrap_or(None),
+        None => None,
+    };
+
+    if let (Some(current_user), Some(r)) = (auth::current_user(&state, &jar).await, &riddle) {
+        scoring::mark_shown(&state.db, &[r.id], now_utc()).await;
+
+        let mut ctx = tera::Context::new();
+        // 每个玩家看到的选项顺序由灯谜的设置决定，同一玩家每次打开相同
+        let options = options::for_player(serde_json::from_str(&r.options_json).unwrap_or_default(), r.option_order, r.option_seed, r.id, current_user.id);
+        let mut riddle_val = json!(r);
+        riddle_val["options"] = json!(options);
+        riddle_val["tags"] = json!(catalog::parse_tags(&r.tags_json));
+        
+        ctx.insert("riddle", &riddle_val);
+        ctx.insert("hints", &hint_views(&state, current_user.id, r).await);
+        ctx.insert("user", &current_user);
+        if let Ok(html) = state.tera.render("question.html", &ctx) {
+            return Html(html).into_response();
+        }
+    }
+
+    // 登录页展示灯谜所属的活动
+    let activity_id = riddle.as_ref().and_then(|r| r.activity_id)
+        .or_else(|| params.get("activity_id").and_then(|id| id.parse::<i64>().ok()));
+    let activity = activity::resolve(&state.db, activity_id).await;
+    let mut ctx = tera::Context::new();
+    ctx.insert("activity", &activity);
+    ctx.insert("riddle_id", &riddle_id.unwrap_or(0));
+    
+    match state.tera.render("index.html", &ctx) {
+        Ok(html) => Html(html).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, format!("Template error: {}", e)).into_response(),
+    }
+}
+
+pub async fn login(
+    State(state): State<Arc<AppState>>,
+    jar: CookieJar,
+    mut multipart: Multipart,
+) -> impl IntoResponse {
+    let mut username = String::new();
+    let mut avatar_path = String::new();
+
+    tracing::debug!("Login attempt started");
+
+    while let Ok(Some(field)) = multipart.next_field().await {
+        let name = field.name().unwrap_or_default().to_string();
+        tracing::debug!("Field name: {}", name);
+        if name == "username" {
+            username = field.text().await.unwrap_or_default();
+            tracing::debug!("Username: {}", username);
+        } else if name == "file" {
+            let filename = field.file_name().unwrap_or_default().to_string();
+            if !filename.is_empty() {
+                let data = field.bytes().await.unwrap_or_default();
+                let today = to_local(now_utc(), state.config.timezone).format("%Y/%m/%d").to_string();
+                
+                // 保存到配置的模板目录下的 avatar 文件夹，由静态资源处理器对外提供
+                let mut upload_dir = state.config.template_dir.join("avatar");
+                upload_dir.push(&today);
+                
+                if fs::create_dir_all(&upload_dir).await.is_err() {
+                    continue;
+                }
+
+                let ext = std::path::Path::new(&filename)
+                    .extension()
+                    .and_then(|s| s.to_str())
+                    .unwrap_or("png");
+                let new_filename = format!("{}.{}", Uuid::new_v4().simple(), ext);
+                let file_path = upload_dir.join(&new_filename);
+                
+                if fs::write(file_path, data).await.is_ok() {
+                    avatar_path = format!("/avatar/{}/{}", today, new_filename);
+                    tracing::debug!("Avatar saved: {}", avatar_path);
+                }
+            }
+        }
+    }
+
+    if username.is_empty() {
+        return (StatusCode::BAD_REQUEST, Json(json!({ "error": "昵称不能为空" }))).into_response();
+    }
+
+    // Generate unique code (8-character hex from UUID)
+    let user_code = Uuid::new_v4().simple().to_string()[..8].to_uppercase();
+    // 会话 token，服务端校验其有效期，退出登录或删除用户后立即失效
+    let token = Uuid::new_v4().to_string();
+    let now = now_utc();
+    let token_expires = now + chrono::Duration::days(auth::PLAYER_SESSION_TTL_DAYS);
+
+    let result = sqlx::query(
+        "INSERT INTO users (username, avatar, user_code, token, register_time, token_expires) VALUES (?, ?, ?, ?, ?, ?)"
+    )
+    .bind(&username)
+    .bind(&avatar_path)
+    .bind(&user_code)
+    .bind(&token)
+    .bind(now)
+    .bind(token_expires)
+    .execute(&state.db)
+    .await;
+
+    match result {
+        Ok(res) => {
+            let user_id = res.last_insert_rowid();
+            let user: User = sqlx::query_as("SELECT * FROM users WHERE id = ?")
+                .bind(user_id)
+                .fetch_one(&state.db)
+                .await
+                .unwrap();
+
+            let cookie = auth::player_session_cookie(&state.session_key, &token);
+
+            (jar.add(cookie), Json(json!({
+                "msg": "登录成功",
+                "user_info": user
+            }))).into_response()
+        }
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({ "error": format!("数据库错误: {}", e) }))).into_response(),
+    }
+}
+
+pub async fn logout(
+    State(state): State<Arc<AppState>>,
+    jar: CookieJar,
+) -> impl IntoResponse {
+    // 服务端注销 token，旧 cookie 即使被保留也无法再使用
+    if let Some(user) = auth::current_user(&state, &jar).await {
+        let _ = sqlx::query("UPDATE users SET token_expires = ? WHERE id = ?")
+            .bind(now_utc())
+            .bind(user.id)
+            .execute(&state.db)
+            .await;
+    }
+    (jar.add(auth::expired_player_session_cookie()), Json(json!({ "msg": "已退出登录" }))).into_response()
+}
+
+/// 玩家在某道灯谜上的提示状态
+async fn hint_views(state: &AppState, user_id: i64, riddle: &Riddle) -> Vec<hints::HintView> {
+    let revealed = hints::revealed_indexes(&state.db, user_id, riddle.id).await;
+    hints::views(&hints::parse_hints(&riddle.hints_json), riddle.shown_at, &revealed, now_utc(), state.config.timezone)
+}
+
+async fn released_riddle(db: &SqlitePool, riddle_id: i64) -> Option<Riddle> {
+    sqlx::query_as(&format!("SELECT * FROM riddles r WHERE r.id = ? AND {}", rounds::RELEASED))
+        .bind(riddle_id)
+        .bind(now_utc())
+        .fetch_optional(db)
+        .await
+        .unwrap_or(None)
+}
+
+#[derive(Deserialize)]
+pub struct HintParams {
+    pub riddle_id: i64,
+}
+
+/// 当前玩家在某道灯谜上的提示，未揭晓的提示不返回内容
+pub async fn get_hints(
+    State(state): State<Arc<AppState>>,
+    jar: CookieJar,
+    Query(params): Query<HintParams>,
+) -> impl IntoResponse {
+    let current_user = match auth::current_user(&state, &jar).await {
+        Some(u) => u,
+        None => return (StatusCode::UNAUTHORIZED, Json(json!({ "error": "登录已失效，请重新登录", "code": "SESSION_INVALID" }))).into_response(),
+    };
+    match released_riddle(&state.db, params.riddle_id).await {
+        Some(r) => Json(json!({ "code": 200, "data": hint_views(&state, current_user.id, &r).await })).into_response(),
+        None => Json(json!({ "msg": "题目不存在", "code": 404 })).into_response(),
+    }
+}
+
+#[derive(Deserialize)]
+pub struct RevealHintPayload {
+    pub riddle_id: i64,
+    /// 提示序号，从 0 开始；必须先揭晓前面的提示
+    pub index: usize,
+}
+
+/// 玩家提前揭晓一条提示，猜中时从本题得分中扣除该提示的积分
+pub async fn reveal_hint(
+    State(state): State<Arc<AppState>>,
+    jar: CookieJar,
+    payload: MaybeFormOrJson<RevealHintPayload>,
+) -> impl IntoResponse {
+    let current_user = match auth::current_user(&state, &jar).await {
+        Some(u) => u,
+        None => return (StatusCode::UNAUTHORIZED, Json(json!({ "error": "登录已失效，请重新登录", "code": "SESSION_INVALID" }))).into_response(),
+    };
+    let RevealHintPayload { riddle_id, index } = payload.0;
+    let riddle = match released_riddle(&state.db, riddle_id).await {
+        Some(r) => r,
+        None => return Json(json!({ "msg": "题目不存在", "code": 404 })).into_response(),
+    };
+    if riddle.is_solved {
+        return Json(json!({ "msg": "这道题已经被猜中了", "code": 400 })).into_response();
+    }
+    // 活动进行中才能揭晓，与作答的限制一致
+    let activity = match riddle.activity_id {
+        Some(id) => activity::find(&state.db, id).await,
+        None => None,
+    };
+    if activity.is_some_and(|a| activity::state_at(&a, now_utc()) != ActivityState::Running) {
+        return Json(json!({ "msg": "活动不在进行中，暂时不能揭晓提示", "code": 400 })).into_response();
+    }
+
+    let views = hint_views(&state, current_user.id, &riddle).await;
+    let view = match views.get(index) {
+        Some(v) => v,
+        None => return Json(json!({ "msg": "提示不存在", "code": 404 })).into_response(),
+    };
+    if view.revealed {
+        return Json(json!({ "code": 200, "msg": "提示已揭晓", "data": views })).into_response();
+    }
+    if !view.can_reveal {
+        return Json(json!({ "msg": "请先揭晓前面的提示", "code": 400 })).into_response();
+    }
+
+    let cost = view.cost;
+    if let Err(e) = hints::reveal(&state.db, current_user.id, riddle_id, index, cost, now_utc()).await {
+        return Json(json!({ "msg": format!("揭晓失败: {}", e), "code": 500 })).into_response();
+    }
+    let msg = if cost > 0 { format!("已揭晓提示，猜中本题时扣除 {} 分", cost) } else { "已揭晓提示".to_string() };
+    Json(json!({ "code": 200, "msg": msg, "data": hint_views(&state, current_user.id, &riddle).await })).into_response()
+}
+
+#[derive(Deserialize)]
+pub struct GuessPayload {
+    pub riddle_id: serde_json::Value,
+    pub answer: String,
+}
+
+pub async fn guess(
+    State(state): State<Arc<AppState>>,
+    jar: CookieJar,
+    payload: MaybeFormOrJson<GuessPayload>,
+) -> impl IntoResponse {
+    if jar.get(auth::PLAYER_SESSION_COOKIE).is_none() {
+        return (StatusCode::UNAUTHORIZED, Json(json!({ "error": "未登录", "code": "NOT_LOGGED_IN" }))).into_response();
+    }
+
+    let current_user = match auth::current_user(&state, &jar).await {
+        Some(u) => u,
+        None => return (StatusCode::UNAUTHORIZED, Json(json!({ "error": "登录已失效，请重新登录", "code": "SESSION_INVALID" }))).into_response(),
+    };
+
+    let GuessPayload { riddle_id: riddle_id_val, answer } = payload.0;
+    let user_answer = answer.trim();
+
+    let riddle_id = if let Some(id) = riddle_id_val.as_i64() {
+        Some(id)
+    } else if let Some(s) = riddle_id_val.as_str() {
+        s.parse::<i64>().ok()
+    } else {
+        None
+    };
+
+    if riddle_id.is_none() || user_answer.is_empty() {
+        return Json(json!({ "msg": "参数不全", "code": 400 })).into_response();
+    }
+    let riddle_id = riddle_id.unwrap();
+
+    let riddle: Option<RiddleWithSolver> = sqlx::query_as(
+        "SELECT r.*, u.username as solver_name, u.avatar as solver_avatar FROM riddles r LEFT JOIN users u ON r.solver_id = u.id WHERE r.id = ?"
+    )
+    .bind(riddle_id)
+    .fetch_optional(&state.db)
+    .await
+    .unwrap_or(None);
+
+    let riddle = match riddle {
+        Some(r) => r,
+        None => return Json(json!({ "msg": "题目不存在", "code": 404 })).into_response(),
+    };
+
+    // 按灯谜所属活动的时间判断，多个活动可以同时进行
+    let activity: Option<Activity> = match riddle.activity_id {
+        Some(id) => activity::find(&state.db, id).await,
+        None => None,
+    };
+
+    if let Some(act) = &activity {
+        match activity::state_at(act, now_utc()) {
+            ActivityState::Scheduled => return Json(json!({ "msg": "活动尚未开始", "code": 400 })).into_response(),
+            ActivityState::Ended => return Json(json!({ "msg": "活动已经结束", "code": 400 })).into_response(),
+            ActivityState::Paused => return Json(json!({ "msg": "主持人暂停了答题，请稍候再试", "code": 400, "paused": true, "can_retry": true })).into_response(),
+            ActivityState::Running => {}
+        }
+    }
+
+    if !rounds::is_released(&state.db, riddle.round_id, now_utc()).await {
+        return Json(json!({ "msg": "这道灯谜所在的轮次尚未开放", "code": 400 })).into_response();
+    }
+
+    if riddle.is_solved {
+        return Json(json!({
+            "success": false,
+            "msg": format!("太可惜了，这道题已经被 {} 抢先猜中了！", riddle.solver_name.unwrap_or_else(|| "别人".to_string())),
+            "code": 400
+        })).into_response();
+    }
+
+    let now_time = now_utc();
+
+    // 作答次数与间隔按活动设置限制，不属于任何活动的灯谜不限；检查通过的同时写入作答记录
+    let max_attempts = activity.as_ref().map(|a| a.max_attempts).unwrap_or(0);
+    let is_correct = answer::is_correct(user_answer, &riddle.answer, &answer::parse_aliases(&riddle.aliases_json), riddle.match_mode);
+    let (record_id, attempts) = match record_attempt(&state.db, current_user.id, riddle_id, user_answer, is_correct, now_time, activity.as_ref()).await {
+        Ok(Ok(recorded)) => recorded,
+        Ok(Err(AttemptLimit::Exhausted)) => {
+            return Json(json!({ "success": false, "msg": format!("每道题最多作答 {} 次，你的机会已经用完了！", max_attempts), "code": 400, "can_retry": false })).into_response();
+        }
+        Ok(Err(AttemptLimit::Cooldown(wait))) => {
+            return Json(json!({ "success": false, "msg": format!("作答太频繁，请 {} 秒后再试", wait), "code": 400, "can_retry": true, "retry_after": wait })).into_response();
+        }
+        Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({ "error": format!("记录作答失败: {}", e) }))).into_response(),
+    };
+
+    // 每次作答都实时推送到管理后台
+    let attempt = |result: &'static str, is_correct: bool, points: i64| events::GuessAttempt {
+        activity_id: riddle.activity_id,
+        riddle_id,
+        riddle_question: riddle.question.clone(),
+        user_id: current_user.id,
+        username: current_user.username.clone(),
+        answer: user_answer.to_string(),
+        normalized_answer: answer::normalize(user_answer),
+        is_correct,
+        result,
+        points,
+        time: format_local(now_time, state.config.timezone),
+    };
+
+    // 忽略空白、标点、全半角与大小写，备选答案同样算答对；按灯谜设置另外接受繁体或拼音
+    if is_correct {
+        // 提前揭晓提示的积分从本题得分中扣除
+        let hint_cost = hints::total_cost(&state.db, current_user.id, riddle_id).await;
+        let points = (scoring::points_for(riddle.points, riddle.difficulty, riddle.shown_at, activity.as_ref(), now_time) - hint_cost).max(0);
+        match claim_riddle(&state.db, current_user.id, riddle_id, record_id, points).await {
+            Ok(true) => {}
+            Ok(false) => {
+                events::guess_attempt(&state.io, attempt("too_late", true, 0));
+                return Json(json!({ "success": false, "msg": "手慢了，已被抢答！", "code": 400 })).into_response();
+            }
+            Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({ "error": format!("更新失败: {}", e) }))).into_response(),
+        }
+
+        events::guess_attempt(&state.io, attempt("solved", true, points));
+        events::emit(&state.io, riddle.activity_id, events::RIDDLE_SOLVED, events::RiddleSolved {
+            activity_id: riddle.activity_id,
+            riddle_id,
+            solver_id: current_user.id,
+            solver_name: current_user.username.clone(),
+            solver_avatar: current_user.avatar.clone(),
+            points,
+        });
+        events::leaderboard_changed(&state, riddle.activity_id).await;
+
+        Json(json!({ "success": true, "msg": format!("恭喜你！抢答成功！获得 {} 积分", points), "code": 200, "points": points })).into_response()
+    } else {
+        events::guess_attempt(&state.io, attempt("wrong", false, 0));
+
+        if max_attempts > 0 {
+            let remaining = max_attempts - attempts - 1;
+            let msg = if remaining > 0 {
+                format!("答案不对，请再接再厉！还剩 {} 次机会", remaining)
+            } else {
+                "答案不对，这道题的作答机会已经用完了".to_string()
+            };
+            return Json(json!({ "success": false, "msg": msg, "can_retry": remaining > 0, "remaining_attempts": remaining })).into_response();
+        }
+        Json(json!({ "success": false, "msg": "答案不对，请再接再厉！", "can_retry": true })).into_response()
+    }
+}
+
+/// 作答超出活动的限制
+enum AttemptLimit {
+    /// 作答次数已用完
+    Exhausted,
+    /// 距上次作答不足间隔，还需等待的秒数
+    Cooldown(i64),
+}
+
+/// 检查作答次数与间隔并写入作答记录 (未抢到灯谜)，返回记录 ID 与此前的作答次数
+///
+/// 检查前先以一次空更新取得写锁，同一玩家并发的作答依次检查，不会超出次数或跳过间隔；
+/// 事务在出错或请求中断时随 `tx` 丢弃自动回滚，连接不会带着未结束的写事务回到连接池
+async fn record_attempt(
+    db: &SqlitePool,
+    user_id: i64,
+    riddle_id: i64,
+    submitted: &str,
+    is_correct: bool,
+    time: NaiveDateTime,
+    activity: Option<&Activity>,
+) -> Result<Result<(i64, i64), AttemptLimit>, sqlx::Error> {
+    let max_attempts = activity.map(|a| a.max_attempts).unwrap_or(0);
+    let cooldown_secs = activity.map(|a| a.cooldown_secs).unwrap_or(0);
+
+    let mut tx = db.begin().await?;
+    sqlx::query("UPDATE riddles SET id = id WHERE id = ?").bind(riddle_id).execute(&mut *tx).await?;
+    let (attempts, last_attempt): (i64, Option<NaiveDateTime>) = sqlx::query_as("SELECT COUNT(*), MAX(solve_time) FROM guess_records WHERE user_id = ? AND riddle_id = ?")
+        .bind(user_id)
+        .bind(riddle_id)
+        .fetch_one(&mut *tx)
+        .await?;
+    if max_attempts > 0 && attempts >= max_attempts {
+        return Ok(Err(AttemptLimit::Exhausted));
+    }
+    if let Some(last) = last_attempt {
+        let wait = cooldown_secs - (time - last).num_seconds();
+        if cooldown_secs > 0 && wait > 0 {
+            return Ok(Err(AttemptLimit::Cooldown(wait)));
+        }
+    }
+    let res = sqlx::query("INSERT INTO guess_records (user_id, riddle_id, is_solved, is_correct, answer, normalized_answer, solve_time) VALUES (?, ?, 0, ?, ?, ?, ?)")
+        .bind(user_id)
+        .bind(riddle_id)
+        .bind(is_correct)
+        .bind(submitted)
+        .bind(answer::normalize(submitted))
+        .bind(time)
+        .execute(&mut *tx)
+        .await?;
+    tx.commit().await?;
+    Ok(Ok((res.last_insert_rowid(), attempts)))
+}
+
+/// 抢答：在同一事务中以条件更新认领灯谜，并把本次的作答记录标为中奖
+///
+/// 只有把 `is_solved` 从 0 改为 1 的请求才算抢到，返回 `Ok(false)` 表示已被别人抢先
+async fn claim_riddle(db: &SqlitePool, user_id: i64, riddle_id: i64, record_id: i64, points: i64) -> Result<bool, sqlx::Error> {
+    let mut tx = db.begin().await?;
+    let claimed = sqlx::query("UPDATE riddles SET is_solved = 1, solver_id = ? WHERE id = ? AND is_solved = 0")
+        .bind(user_id)
+        .bind(riddle_id)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected() == 1;
+    if !claimed {
+        tx.rollback().await?;
+        return Ok(false);
+    }
+
+    sqlx::query("UPDATE guess_records SET is_solved = 1, points = ? WHERE id = ?")
+        .bind(points)
+        .bind(record_id)
+        .execute(&mut *tx)
+        .await?;
+    tx.commit().await?;
+    Ok(true)
+}
+
+pub async fn get_my_records(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<ActivityParams>,
+    jar: CookieJar,
+) -> impl IntoResponse {
+    let user_id = match auth::current_user(&state, &jar).await {
+        Some(u) => u.id,
+        None => return (StatusCode::UNAUTHORIZED, Json(json!({ "error": "未登录" }))).into_response(),
+    };
+
+    // 可以多次作答，灯谜被猜中之前不返回正确答案
+    let records: Vec<GuessRecordWithInfo> = sqlx::query_as(
+        "SELECT gr.*, u.username as user_name, r.question as riddle_question,
+                CASE WHEN r.is_solved = 1 THEN r.answer END as riddle_answer
+         FROM guess_records gr
+         JOIN riddles r ON gr.riddle_id = r.id
+         JOIN users u ON gr.user_id = u.id
+         WHERE gr.user_id = ? AND (? IS NULL OR r.activity_id = ?)
+         ORDER BY gr.solve_time DESC"
+    )
+    .bind(user_id)
+    .bind(params.activity_id)
+    .bind(params.activity_id)
+    .fetch_all(&state.db)
+    .await
+    .unwrap_or_default();
+
+    let result: Vec<serde_json::Value> = records.into_iter().map(|rec| {
+        let mut val = json!(rec);
+        if let Some(st) = rec.solve_time {
+            val["solve_time"] = json!(format_local(st, state.config.timezone));
+        }
+        val
+    }).collect();
+
+    Json(result).into_response()
+}
+
+#[derive(Deserialize)]
+pub struct LeaderboardParams {
+    pub activity_id: Option<i64>,
+    /// 返回前几名，默认 10，最多 100
+    pub limit: Option<u32>,
+}
+
+/// 公开的积分排行榜 (只读)，供大屏轮询
+pub async fn get_leaderboard(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<LeaderboardParams>,
+) -> impl IntoResponse {
+    let activity = match activity::resolve(&state.db, params.activity_id).await {
+        Some(a) => a,
+        None => return Json(json!({ "code": 404, "message": "活动不存在" })).into_response(),
+    };
+    let limit = params.limit.unwrap_or(scoring::DEFAULT_LEADERBOARD_SIZE).clamp(1, 100);
+
+    match scoring::leaderboard(&state.db, &activity, limit, state.config.timezone).await {
+        Ok(data) => Json(json!({ "code": 200, "message": "success", "data": data })).into_response(),
+        Err(e) => Json(json!({ "code": 500, "message": format!("查询失败: {}", e) })).into_response(),
+    }
+}
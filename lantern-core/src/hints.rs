@@ -0,0 +1,122 @@
+use chrono::{Duration, FixedOffset, NaiveDateTime};
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use crate::utils::format_local;
+
+/// 每道灯谜最多的提示数
+pub const MAX_HINTS: usize = 5;
+/// 单条提示的最大长度 (字符)
+pub const MAX_HINT_LEN: usize = 200;
+
+/// 灯谜的一条提示，按顺序逐条揭晓
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Hint {
+    pub text: String,
+    /// 灯谜展示后多少分钟仍未被猜中时自动揭晓，为空表示不自动揭晓
+    #[serde(default)]
+    pub after_minutes: Option<i64>,
+    /// 玩家提前揭晓需要扣除的积分，从猜中这道灯谜的得分中扣除
+    #[serde(default)]
+    pub cost: i64,
+}
+
+/// 玩家看到的提示状态，未揭晓时不返回内容
+#[derive(Debug, Serialize)]
+pub struct HintView {
+    pub index: usize,
+    pub text: Option<String>,
+    pub cost: i64,
+    /// 已揭晓：自动揭晓或玩家自己揭晓
+    pub revealed: bool,
+    /// 可以揭晓：前面的提示都已揭晓
+    pub can_reveal: bool,
+    /// 自动揭晓的时间 (活动时区)，灯谜还没有展示过或不自动揭晓时为空
+    pub auto_reveal_at: Option<String>,
+}
+
+pub fn parse_hints(hints_json: &str) -> Vec<Hint> {
+    serde_json::from_str(hints_json).unwrap_or_default()
+}
+
+/// 整理后台提交的提示：去掉空白项，分钟数不大于 0 时视为不自动揭晓，积分不为负
+pub fn clean_hints(hints: Vec<Hint>) -> Vec<Hint> {
+    hints
+        .into_iter()
+        .filter_map(|h| {
+            let text: String = h.text.trim().chars().take(MAX_HINT_LEN).collect();
+            if text.is_empty() {
+                return None;
+            }
+            Some(Hint {
+                text,
+                after_minutes: h.after_minutes.filter(|m| *m > 0),
+                cost: h.cost.max(0),
+            })
+        })
+        .take(MAX_HINTS)
+        .collect()
+}
+
+/// 自动揭晓的时间：从灯谜首次展示开始计时
+pub fn auto_reveal_at(hint: &Hint, shown_at: Option<NaiveDateTime>) -> Option<NaiveDateTime> {
+    Some(shown_at? + Duration::minutes(hint.after_minutes?))
+}
+
+/// 按顺序计算每条提示对玩家的状态，`revealed` 为玩家自己揭晓过的序号
+pub fn views(hints: &[Hint], shown_at: Option<NaiveDateTime>, revealed: &[i64], now: NaiveDateTime, tz: FixedOffset) -> Vec<HintView> {
+    let mut previous_revealed = true;
+    hints
+        .iter()
+        .enumerate()
+        .map(|(index, hint)| {
+            let auto_at = auto_reveal_at(hint, shown_at);
+            let is_revealed = revealed.contains(&(index as i64)) || auto_at.is_some_and(|t| t <= now);
+            let view = HintView {
+                index,
+                text: is_revealed.then(|| hint.text.clone()),
+                cost: hint.cost,
+                revealed: is_revealed,
+                can_reveal: !is_revealed && previous_revealed,
+                auto_reveal_at: auto_at.filter(|_| !is_revealed).map(|t| format_local(t, tz)),
+            };
+            previous_revealed = is_revealed;
+            view
+        })
+        .collect()
+}
+
+/// 玩家在某道灯谜上自己揭晓过的提示序号
+pub async fn revealed_indexes(pool: &SqlitePool, user_id: i64, riddle_id: i64) -> Vec<i64> {
+    sqlx::query_as::<_, (i64,)>("SELECT hint_index FROM hint_reveals WHERE user_id = ? AND riddle_id = ? ORDER BY hint_index")
+        .bind(user_id)
+        .bind(riddle_id)
+        .fetch_all(pool)
+        .await
+        .unwrap_or_default()
+        .into_iter()
+        .map(|(i,)| i)
+        .collect()
+}
+
+/// 记录玩家揭晓提示，重复揭晓不再扣分
+pub async fn reveal(pool: &SqlitePool, user_id: i64, riddle_id: i64, index: usize, cost: i64, now: NaiveDateTime) -> Result<(), sqlx::Error> {
+    sqlx::query("INSERT OR IGNORE INTO hint_reveals (user_id, riddle_id, hint_index, cost, revealed_at) VALUES (?, ?, ?, ?, ?)")
+        .bind(user_id)
+        .bind(riddle_id)
+        .bind(index as i64)
+        .bind(cost)
+        .bind(now)
+        .execute(pool)
+        .await
+        .map(|_| ())
+}
+
+/// 玩家在某道灯谜上揭晓提示共需扣除的积分
+pub async fn total_cost(pool: &SqlitePool, user_id: i64, riddle_id: i64) -> i64 {
+    sqlx::query_scalar("SELECT COALESCE(SUM(cost), 0) FROM hint_reveals WHERE user_id = ? AND riddle_id = ?")
+        .bind(user_id)
+        .bind(riddle_id)
+        .fetch_one(pool)
+        .await
+        .unwrap_or(0)
+}
@@ -0,0 +1,609 @@
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use sqlx::{QueryBuilder, Sqlite, SqlitePool};
+use crate::{answer, catalog, hints, media::{self, Bundle}, models::{MediaType, OptionOrder}, options, scoring, sheet};
+
+/// 导入表格的列，按表头识别或按列映射指定；序列化为 `question` 等英文名
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Column {
+    ExternalId,
+    Question,
+    Answer,
+    Remark,
+    Options,
+    Aliases,
+    Points,
+    Difficulty,
+    Category,
+    Tags,
+    Hints,
+    Media,
+}
+
+impl Column {
+    pub const ALL: [Column; 12] = [
+        Column::ExternalId,
+        Column::Question,
+        Column::Answer,
+        Column::Remark,
+        Column::Options,
+        Column::Aliases,
+        Column::Points,
+        Column::Difficulty,
+        Column::Category,
+        Column::Tags,
+        Column::Hints,
+        Column::Media,
+    ];
+
+    /// 后台展示用的中文列名
+    pub fn label(self) -> &'static str {
+        match self {
+            Column::ExternalId => "编号",
+            Column::Question => "灯谜题目",
+            Column::Answer => "正确答案",
+            Column::Remark => "描述",
+            Column::Options => "选项",
+            Column::Aliases => "备选答案",
+            Column::Points => "分值",
+            Column::Difficulty => "难度",
+            Column::Category => "分类",
+            Column::Tags => "标签",
+            Column::Hints => "提示",
+            Column::Media => "媒体",
+        }
+    }
+
+    /// 按表头识别列，不认识的列忽略；除了模板中的中文表头，也认识常见的别名 (谜面、谜底等) 以及与 `Riddle` 字段同名的英文表头
+    pub fn of(header: &str) -> Option<Column> {
+        match header.trim().to_lowercase().as_str() {
+            "编号" | "外部编号" | "external_id" => Some(Column::ExternalId),
+            "灯谜题目" | "谜面" | "题目" | "question" | "riddle" => Some(Column::Question),
+            "正确答案" | "谜底" | "答案" | "answer" => Some(Column::Answer),
+            "描述" | "谜目" | "备注" | "说明" | "remark" | "description" | "note" => Some(Column::Remark),
+            "分值" | "分数" | "points" | "score" => Some(Column::Points),
+            "难度" | "difficulty" | "level" => Some(Column::Difficulty),
+            "分类" | "类别" | "category" => Some(Column::Category),
+            "媒体" | "图片" | "音频" | "media" | "image" | "audio" => Some(Column::Media),
+            // 英文表头只按字段名精确匹配，`option_order` 等同前缀的字段不是选项
+            "options" | "options_json" => Some(Column::Options),
+            "aliases" | "aliases_json" => Some(Column::Aliases),
+            "tags" | "tags_json" => Some(Column::Tags),
+            "hints" | "hints_json" => Some(Column::Hints),
+            // 标签、提示、备选答案、选项可以分多列填写，如 `选项1`、`提示2`
+            h if h.contains("标签") => Some(Column::Tags),
+            h if h.starts_with("提示") => Some(Column::Hints),
+            h if h.contains("备选答案") => Some(Column::Aliases),
+            h if h.contains("选项") => Some(Column::Options),
+            _ => None,
+        }
+    }
+
+    /// 导入模板中该列的表头：选项、标签与提示分多列填写
+    pub fn template_headers(self) -> Vec<String> {
+        let width = match self {
+            Column::Options => 4,
+            Column::Tags | Column::Hints => 2,
+            _ => return vec![self.label().to_string()],
+        };
+        (1..=width).map(|i| format!("{}{}", self.label(), i)).collect()
+    }
+
+    pub fn required(self) -> bool {
+        matches!(self, Column::Question | Column::Answer)
+    }
+
+    /// 填写说明，导入模板中作为单元格的输入提示 (Excel 限制 255 个字符)
+    pub fn note(self) -> String {
+        match self {
+            Column::ExternalId => "外部系统中的编号，按编号判断重复时每行都要填写".to_string(),
+            Column::Question => "灯谜的谜面".to_string(),
+            Column::Answer => format!("正确答案；{}", options::MISSING_ANSWER_RULE),
+            Column::Remark => "谜目等说明，显示在谜面后，如“打一字”".to_string(),
+            Column::Options => "每列一个选项，表头含“选项”的列都按选项导入，可以增减列".to_string(),
+            Column::Aliases => "同样算答对的其它答案，同一格中用 | 分隔".to_string(),
+            Column::Points => format!("猜中的基础分值，不小于 0 的整数，默认 {}", scoring::DEFAULT_POINTS),
+            Column::Difficulty => format!("{}~{} 的整数，默认 {}", scoring::MIN_DIFFICULTY, scoring::MAX_DIFFICULTY, scoring::MIN_DIFFICULTY),
+            Column::Category => format!("不超过 {} 个字", catalog::MAX_LABEL_LEN),
+            Column::Tags => format!("每列一个标签，或在同一格中用逗号、顿号或 | 分隔，最多 {} 个", catalog::MAX_TAGS),
+            Column::Hints => format!("按列的顺序逐条揭晓，玩家可免费揭晓，最多 {} 条", hints::MAX_HINTS),
+            Column::Media => format!(
+                "zip 包内的图片或音频文件名，表格与文件一起打包上传；图片支持 {}，音频支持 {}",
+                media::IMAGE_EXTENSIONS.join("/"),
+                media::AUDIO_EXTENSIONS.join("/")
+            ),
+        }
+    }
+
+    fn template_rule(self) -> sheet::Rule {
+        match self {
+            Column::Points => sheet::Rule::WholeAtLeast(0),
+            Column::Difficulty => sheet::Rule::List((scoring::MIN_DIFFICULTY..=scoring::MAX_DIFFICULTY).map(|d| d.to_string()).collect()),
+            _ => sheet::Rule::Any,
+        }
+    }
+}
+
+/// 导入模板中的示例灯谜，未列出的列留空
+const TEMPLATE_EXAMPLES: &[&[(Column, &[&str])]] = &[
+    &[
+        (Column::ExternalId, &["A001"]),
+        (Column::Question, &["一口咬掉牛尾巴"]),
+        (Column::Answer, &["告"]),
+        (Column::Remark, &["打一字"]),
+        (Column::Options, &["告", "吉", "舌", "言"]),
+        (Column::Points, &["10"]),
+        (Column::Difficulty, &["1"]),
+        (Column::Category, &["字谜"]),
+        (Column::Tags, &["入门", "动物"]),
+        (Column::Hints, &["与说话有关", "“牛”字去掉最后一笔"]),
+    ],
+    &[
+        (Column::ExternalId, &["A002"]),
+        (Column::Question, &["画时圆，写时方，冬时短，夏时长"]),
+        (Column::Answer, &["日"]),
+        (Column::Remark, &["打一字"]),
+        (Column::Options, &["日", "月", "口", "田"]),
+        (Column::Points, &["20"]),
+        (Column::Difficulty, &["2"]),
+        (Column::Category, &["字谜"]),
+        (Column::Tags, &["天文"]),
+        (Column::Hints, &["天上有一个"]),
+    ],
+];
+
+/// 按导入时的列定义生成模板：第一个工作表为表头与示例灯谜，第二个工作表为各列的填写说明
+pub fn template() -> Vec<sheet::Worksheet> {
+    let text = |s: &str| sheet::Cell::Text(s.to_string());
+    let mut headers = Vec::new();
+    let mut widths = Vec::new();
+    let mut validations = Vec::new();
+    for column in Column::ALL {
+        for header in column.template_headers() {
+            validations.push(sheet::Validation {
+                column: headers.len(),
+                rule: column.template_rule(),
+                title: if column.required() { format!("{} (必填)", header) } else { header.clone() },
+                prompt: column.note(),
+            });
+            widths.push(if column == Column::Question { 32.0 } else { 12.0 });
+            headers.push(text(&header));
+        }
+    }
+
+    let mut rows = vec![headers];
+    for example in TEMPLATE_EXAMPLES {
+        let mut row = Vec::new();
+        for column in Column::ALL {
+            let values = example.iter().find(|(c, _)| *c == column).map(|(_, v)| *v).unwrap_or_default();
+            for i in 0..column.template_headers().len() {
+                let value = values.get(i).copied().unwrap_or_default();
+                row.push(match value.parse::<f64>() {
+                    Ok(n) if matches!(column, Column::Points | Column::Difficulty) => sheet::Cell::Number(n),
+                    _ => text(value),
+                });
+            }
+        }
+        rows.push(row);
+    }
+    let riddles = sheet::Worksheet { name: "灯谜".to_string(), rows, widths, validations };
+
+    let mut notes = vec![vec![text("列"), text("必填"), text("说明"), text("英文表头")]];
+    for column in Column::ALL {
+        notes.push(vec![
+            text(&column.template_headers().join("、")),
+            text(if column.required() { "是" } else { "否" }),
+            text(&column.note()),
+            text(&serde_json::to_value(column).ok().and_then(|v| v.as_str().map(str::to_string)).unwrap_or_default()),
+        ]);
+    }
+    notes.push(Vec::new());
+    for line in [
+        "导入时只读取第一个工作表，第一行为表头；列的顺序不限，不认识的表头忽略，也可以在导入时设置列映射",
+        "也可以导入 xls、ods、csv (UTF-8 或 GBK) 与 json 文件，表头相同",
+        "有任何一行出错时不会导入任何灯谜，建议先预检",
+    ] {
+        notes.push(vec![text(line)]);
+    }
+    let notes = sheet::Worksheet { name: "填写说明".to_string(), rows: notes, widths: vec![24.0, 8.0, 60.0, 14.0], validations: Vec::new() };
+
+    vec![riddles, notes]
+}
+
+/// 列映射：表头 (去掉首尾空白) 到列，值为 `null` 表示忽略该列；没有写在映射中的表头按 `Column::of` 识别
+pub type Mapping = BTreeMap<String, Option<Column>>;
+
+/// 按列映射确定每个表头对应的列
+pub fn columns(headers: &[String], mapping: &Mapping) -> Vec<Option<Column>> {
+    headers
+        .iter()
+        .map(|h| match mapping.get(h.trim()) {
+            Some(column) => *column,
+            None => Column::of(h),
+        })
+        .collect()
+}
+
+/// 按表头识别出的列映射，供后台在导入前确认、调整
+pub fn suggest(headers: &[String]) -> Mapping {
+    headers.iter().filter(|h| !h.trim().is_empty()).map(|h| (h.trim().to_string(), Column::of(h))).collect()
+}
+
+/// 遇到已有灯谜时的处理方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportMode {
+    /// 跳过已有的灯谜
+    #[default]
+    Skip,
+    /// 照常新增
+    Append,
+    /// 用表格中的内容更新已有的灯谜
+    Upsert,
+}
+
+/// 判断是否为同一道灯谜的依据
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportKey {
+    /// 谜面相同 (忽略空白、标点与大小写)
+    #[default]
+    Question,
+    /// `编号` 列相同
+    ExternalId,
+}
+
+/// 一次导入的设置
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ImportOptions {
+    pub activity_id: Option<i64>,
+    pub mode: ImportMode,
+    pub key: ImportKey,
+    /// 导入的灯谜的选项顺序
+    pub option_order: OptionOrder,
+    /// 打乱选项用的种子
+    pub option_seed: i64,
+}
+
+/// 表格中一行解析出的灯谜
+#[derive(Debug, Default)]
+pub struct RiddleRow {
+    pub external_id: String,
+    pub question: String,
+    pub answer: String,
+    pub remark: String,
+    pub options: Vec<String>,
+    pub aliases: Vec<String>,
+    pub points: i64,
+    pub difficulty: i64,
+    pub category: String,
+    pub tags: Vec<String>,
+    pub hints: Vec<hints::Hint>,
+    pub media_name: String,
+    /// 表格中有的列，更新已有灯谜时只更新这些列
+    pub columns: Vec<Column>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RowStatus {
+    Insert,
+    Update,
+    Skip,
+    Error,
+}
+
+/// 逐行导入报告
+#[derive(Debug, Serialize)]
+pub struct RowReport {
+    /// 表格中的行号，表头为第 1 行
+    pub row: usize,
+    pub question: String,
+    pub status: RowStatus,
+    /// 更新或跳过时为已有灯谜的 ID，新增后为新灯谜的 ID
+    pub riddle_id: Option<i64>,
+    pub errors: Vec<String>,
+    pub warnings: Vec<String>,
+}
+
+pub struct ImportRow {
+    pub riddle: RiddleRow,
+    pub report: RowReport,
+}
+
+/// 解析表格，跳过空行；`rows` 不含表头，第一行数据为表格第 2 行
+pub fn parse_rows(columns: &[Option<Column>], rows: Vec<Vec<String>>) -> Vec<ImportRow> {
+    rows.into_iter()
+        .enumerate()
+        .filter(|(_, cells)| cells.iter().any(|c| !c.trim().is_empty()))
+        .map(|(i, cells)| parse_row(columns, cells, i + 2))
+        .collect()
+}
+
+fn parse_row(columns: &[Option<Column>], cells: Vec<String>, line: usize) -> ImportRow {
+    let mut riddle = RiddleRow {
+        points: scoring::DEFAULT_POINTS,
+        difficulty: scoring::MIN_DIFFICULTY,
+        columns: columns.iter().flatten().copied().collect(),
+        ..Default::default()
+    };
+    let mut errors = Vec::new();
+    let mut warnings = Vec::new();
+
+    for (i, cell) in cells.into_iter().enumerate() {
+        let Some(column) = columns.get(i).copied().flatten() else { continue };
+        let value = cell.trim();
+        match column {
+            Column::ExternalId => riddle.external_id = value.to_string(),
+            Column::Question => riddle.question = value.to_string(),
+            Column::Answer => riddle.answer = value.to_string(),
+            Column::Remark => riddle.remark = value.to_string(),
+            Column::Points if !value.is_empty() => match value.parse::<f64>() {
+                Ok(p) => riddle.points = (p.round() as i64).max(0),
+                Err(_) => errors.push(format!("分值不是数字: {}", value)),
+            },
+            Column::Difficulty if !value.is_empty() => match value.parse::<f64>() {
+                Ok(d) => {
+                    let d = d.round() as i64;
+                    riddle.difficulty = d.clamp(scoring::MIN_DIFFICULTY, scoring::MAX_DIFFICULTY);
+                    if riddle.difficulty != d {
+                        warnings.push(format!("难度超出 {}~{}，按 {} 导入", scoring::MIN_DIFFICULTY, scoring::MAX_DIFFICULTY, riddle.difficulty));
+                    }
+                }
+                Err(_) => errors.push(format!("难度不是数字: {}", value)),
+            },
+            Column::Points | Column::Difficulty => {}
+            Column::Category => riddle.category = catalog::clean_category(value),
+            // 同一格中用逗号、顿号或 | 分隔
+            Column::Tags => riddle.tags.extend(catalog::split_tags(value)),
+            // 提示按列的顺序逐条揭晓，玩家可以免费揭晓
+            Column::Hints if !value.is_empty() => riddle.hints.push(hints::Hint { text: value.to_string(), after_minutes: None, cost: 0 }),
+            Column::Hints => {}
+            // 同一格中用 | 分隔
+            Column::Aliases => riddle.aliases.extend(value.split('|').map(str::to_string)),
+            Column::Options if !value.is_empty() => riddle.options.push(value.to_string()),
+            Column::Options => {}
+            Column::Media => riddle.media_name = value.to_string(),
+        }
+    }
+
+    if riddle.question.is_empty() {
+        errors.push("缺少灯谜题目".to_string());
+    }
+    if riddle.answer.is_empty() {
+        errors.push("缺少正确答案".to_string());
+    }
+    if riddle.tags.len() > catalog::MAX_TAGS {
+        warnings.push(format!("标签超过 {} 个，多余的被忽略", catalog::MAX_TAGS));
+    }
+    if riddle.hints.len() > hints::MAX_HINTS {
+        warnings.push(format!("提示超过 {} 条，多余的被忽略", hints::MAX_HINTS));
+    }
+    if !riddle.media_name.is_empty() && media::media_type_of(&riddle.media_name).is_none() {
+        errors.push(format!("不支持的媒体格式: {}", riddle.media_name));
+    }
+
+    let report = RowReport { row: line, question: riddle.question.clone(), status: RowStatus::Insert, riddle_id: None, errors, warnings };
+    ImportRow { riddle, report }
+}
+
+fn key_of(riddle: &RiddleRow, key: ImportKey) -> String {
+    match key {
+        ImportKey::Question => answer::normalize(&riddle.question),
+        ImportKey::ExternalId => riddle.external_id.clone(),
+    }
+}
+
+/// 对照活动中已有的灯谜决定每一行新增、更新还是跳过，并检查表格内的重复行与缺失的媒体文件
+pub async fn plan(pool: &SqlitePool, rows: &mut [ImportRow], opts: &ImportOptions, bundle: Option<&Bundle>) -> Result<(), sqlx::Error> {
+    let (mode, key) = (opts.mode, opts.key);
+    let existing: Vec<(i64, String, String, String, OptionOrder, i64)> = sqlx::query_as("SELECT id, question, external_id, options_json, option_order, option_seed FROM riddles WHERE activity_id IS ?")
+        .bind(opts.activity_id)
+        .fetch_all(pool)
+        .await?;
+    let mut existing_ids = HashMap::new();
+    let mut stored_options = HashMap::new();
+    for (id, question, external_id, options_json, option_order, option_seed) in existing {
+        stored_options.insert(id, (options_json, option_order, option_seed));
+        let k = match key {
+            ImportKey::Question => answer::normalize(&question),
+            ImportKey::ExternalId => external_id,
+        };
+        if !k.is_empty() {
+            existing_ids.entry(k).or_insert(id);
+        }
+    }
+
+    let mut seen: HashMap<String, usize> = HashMap::new();
+    for row in rows.iter_mut() {
+        let report = &mut row.report;
+        let k = key_of(&row.riddle, key);
+        if k.is_empty() {
+            if key == ImportKey::ExternalId {
+                report.errors.push("按编号导入时编号不能为空".to_string());
+            }
+        } else if let Some(first) = seen.get(&k) {
+            report.errors.push(format!("与第 {} 行重复", first));
+        } else {
+            seen.insert(k.clone(), report.row);
+        }
+        if !row.riddle.media_name.is_empty() && bundle.is_none_or(|b| b.file(&row.riddle.media_name).is_none()) {
+            report.errors.push(format!("找不到媒体文件 {}", row.riddle.media_name));
+        }
+        if !report.errors.is_empty() {
+            report.status = RowStatus::Error;
+            continue;
+        }
+
+        if let Some(&id) = existing_ids.get(&k) {
+            report.riddle_id = Some(id);
+            match mode {
+                ImportMode::Skip => {
+                    report.status = RowStatus::Skip;
+                    report.warnings.push(format!("已有相同的灯谜 #{}，跳过", id));
+                }
+                ImportMode::Append => {
+                    report.riddle_id = None;
+                    report.warnings.push(format!("与已有的灯谜 #{} 重复", id));
+                }
+                ImportMode::Upsert => report.status = RowStatus::Update,
+            }
+        }
+
+        // 选项的位置取决于顺序与种子，因此在这里而不是解析时补上答案；
+        // 更新时表格没有选项列则沿用已有的选项，谜底变了同样要在其中
+        let riddle = &mut row.riddle;
+        let stored = report.riddle_id.and_then(|id| stored_options.get(&id));
+        let (options, order, seed) = match stored {
+            Some((json, order, seed)) if report.status == RowStatus::Update && !riddle.columns.contains(&Column::Options) => {
+                (serde_json::from_str(json).unwrap_or_default(), *order, *seed)
+            }
+            _ => (std::mem::take(&mut riddle.options), opts.option_order, opts.option_seed),
+        };
+        let (options, added) = options::with_answer(&riddle.answer, options, order, seed);
+        riddle.options = options;
+        if added {
+            report.warnings.push("正确答案不在选项中，已加入选项".to_string());
+        }
+    }
+    Ok(())
+}
+
+/// 导入结果：新增的灯谜、保存的媒体文件与被替换下来的媒体文件 (提交后由调用方清理)
+#[derive(Debug, Default)]
+pub struct Applied {
+    pub inserted_ids: Vec<i64>,
+    pub saved_media: Vec<String>,
+    pub replaced_media: Vec<String>,
+}
+
+/// 在一个事务中写入所有新增、更新的行；任何一行失败都整体回滚，并删除已保存的媒体文件
+pub async fn apply(
+    pool: &SqlitePool,
+    rows: &mut [ImportRow],
+    opts: &ImportOptions,
+    bundle: Option<&Bundle>,
+    template_dir: &Path,
+    date_dir: &str,
+    now: NaiveDateTime,
+) -> Result<Applied, String> {
+    let mut applied = Applied::default();
+    match write(pool, rows, opts, bundle, template_dir, date_dir, now, &mut applied).await {
+        Ok(()) => Ok(applied),
+        Err(e) => {
+            for row in rows.iter_mut().filter(|r| r.report.status == RowStatus::Insert) {
+                row.report.riddle_id = None;
+            }
+            media::remove_unused(pool, template_dir, &applied.saved_media).await;
+            Err(e)
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn write(
+    pool: &SqlitePool,
+    rows: &mut [ImportRow],
+    opts: &ImportOptions,
+    bundle: Option<&Bundle>,
+    template_dir: &Path,
+    date_dir: &str,
+    now: NaiveDateTime,
+    applied: &mut Applied,
+) -> Result<(), String> {
+    let mut tx = pool.begin().await.map_err(|e| e.to_string())?;
+    for row in rows.iter_mut() {
+        let status = row.report.status;
+        if status != RowStatus::Insert && status != RowStatus::Update {
+            continue;
+        }
+        let riddle = &mut row.riddle;
+        let line = row.report.row;
+
+        let media = match (bundle, riddle.media_name.as_str()) {
+            (_, "") => None,
+            (Some(bundle), name) => {
+                let file = bundle.file(name).ok_or_else(|| format!("第 {} 行: 找不到媒体文件 {}", line, name))?;
+                let (media_type, url) = media::save(template_dir, date_dir, name, file).await.map_err(|e| format!("第 {} 行: {}", line, e))?;
+                applied.saved_media.push(url.clone());
+                Some((media_type, url))
+            }
+            (None, name) => return Err(format!("第 {} 行: 找不到媒体文件 {}", line, name)),
+        };
+
+        let options_json = serde_json::to_string(&riddle.options).unwrap();
+        let aliases_json = serde_json::to_string(&answer::clean_aliases(&riddle.answer, std::mem::take(&mut riddle.aliases))).unwrap();
+        let tags_json = serde_json::to_string(&catalog::clean_tags(std::mem::take(&mut riddle.tags))).unwrap();
+        let hints_json = serde_json::to_string(&hints::clean_hints(std::mem::take(&mut riddle.hints))).unwrap();
+
+        let result = if let (RowStatus::Update, Some(id)) = (status, row.report.riddle_id) {
+            // 只更新表格中有的列，没有的列保持原样；编号与媒体为空时也保持原样
+            let has = |column| riddle.columns.contains(&column);
+            let mut qb: QueryBuilder<Sqlite> = QueryBuilder::new("UPDATE riddles SET ");
+            let mut set = qb.separated(", ");
+            set.push("question = ").push_bind_unseparated(&riddle.question);
+            set.push("answer = ").push_bind_unseparated(&riddle.answer);
+            if has(Column::Aliases) {
+                set.push("aliases_json = ").push_bind_unseparated(aliases_json);
+            }
+            if has(Column::Options) {
+                set.push("options_json = ").push_bind_unseparated(options_json);
+                set.push("option_order = ").push_bind_unseparated(opts.option_order);
+                set.push("option_seed = ").push_bind_unseparated(opts.option_seed);
+            } else if !riddle.options.is_empty() {
+                // 沿用的已有选项，已在 `plan` 中补上新的谜底
+                set.push("options_json = ").push_bind_unseparated(options_json);
+            }
+            if has(Column::Remark) {
+                set.push("remark = ").push_bind_unseparated(&riddle.remark);
+            }
+            if has(Column::Points) {
+                set.push("points = ").push_bind_unseparated(riddle.points);
+            }
+            if has(Column::Difficulty) {
+                set.push("difficulty = ").push_bind_unseparated(riddle.difficulty);
+            }
+            if has(Column::Category) {
+                set.push("category = ").push_bind_unseparated(&riddle.category);
+            }
+            if has(Column::Tags) {
+                set.push("tags_json = ").push_bind_unseparated(tags_json);
+            }
+            if has(Column::Hints) {
+                set.push("hints_json = ").push_bind_unseparated(hints_json);
+            }
+            if !riddle.external_id.is_empty() {
+                set.push("external_id = ").push_bind_unseparated(&riddle.external_id);
+            }
+            if let Some((media_type, media_url)) = media {
+                let old_media: String = sqlx::query_scalar("SELECT media_url FROM riddles WHERE id = ?").bind(id).fetch_one(&mut *tx).await.unwrap_or_default();
+                if !old_media.is_empty() {
+                    applied.replaced_media.push(old_media);
+                }
+                set.push("media_type = ").push_bind_unseparated(media_type);
+                set.push("media_url = ").push_bind_unseparated(media_url);
+            }
+            qb.push(" WHERE id = ").push_bind(id);
+            qb.build().execute(&mut *tx).await
+        } else {
+            let (media_type, media_url) = media.unwrap_or_else(|| (MediaType::None, String::new()));
+            let res = sqlx::query("INSERT INTO riddles (question, answer, aliases_json, points, difficulty, category, tags_json, hints_json, media_type, media_url, remark, options_json, option_order, option_seed, external_id, add_time, activity_id) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)")
+                .bind(&riddle.question).bind(&riddle.answer).bind(aliases_json).bind(riddle.points).bind(riddle.difficulty).bind(&riddle.category)
+                .bind(tags_json).bind(hints_json).bind(media_type).bind(media_url).bind(&riddle.remark).bind(options_json)
+                .bind(opts.option_order).bind(opts.option_seed).bind(&riddle.external_id).bind(now).bind(opts.activity_id)
+                .execute(&mut *tx)
+                .await;
+            if let Ok(r) = &res {
+                row.report.riddle_id = Some(r.last_insert_rowid());
+                applied.inserted_ids.push(r.last_insert_rowid());
+            }
+            res
+        };
+        result.map_err(|e| format!("第 {} 行: {}", line, e))?;
+    }
+    tx.commit().await.map_err(|e| e.to_string())?;
+    Ok(())
+}
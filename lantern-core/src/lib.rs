@@ -0,0 +1,320 @@
+use axum::{
+    routing::{get, post},
+    Router,
+    response::{IntoResponse, Response},
+    body::Body,
+    extract::State,
+    http::{header, StatusCode, Uri},
+    middleware,
+};
+use std::path::PathBuf;
+use std::sync::Arc;
+use tower_http::cors::{AllowOrigin, Any, CorsLayer};
+use tera::Tera;
+use socketioxide::SocketIo;
+use rust_embed::RustEmbed;
+
+pub mod activity;
+pub mod answer;
+pub mod auth;
+pub mod catalog;
+pub mod db;
+pub mod events;
+pub mod hints;
+pub mod importer;
+pub mod mappings;
+pub mod media;
+pub mod options;
+pub mod models;
+pub mod query;
+pub mod rounds;
+pub mod scoring;
+pub mod sheet;
+pub mod handlers;
+pub mod utils;
+
+#[derive(RustEmbed)]
+#[folder = "../template/"]
+struct Asset;
+
+/// 后端运行配置，由独立服务端和 Tauri 壳分别构造
+#[derive(Debug, Clone)]
+pub struct Config {
+    /// SQLite 数据库文件路径
+    pub db_path: PathBuf,
+    /// 本地模板目录：上传的头像保存于此，静态资源优先从这里读取
+    pub template_dir: PathBuf,
+    /// 开发模式下直接从 `template_dir` 加载 Tera 模板，而不是使用嵌入资源
+    pub templates_from_disk: bool,
+    /// 本机 (loopback) 访问 `/pro-api` 时免登录并视为超级管理员，供桌面端使用
+    pub trust_local_admin: bool,
+    /// 允许跨域访问的来源，即不与后端同源的管理后台 (如桌面端的 `tauri://localhost`)；为空时只允许同源访问
+    pub admin_origins: Vec<String>,
+    /// 首次启动时默认管理员 `admin` 的密码，未设置时随机生成并打印到日志
+    pub initial_admin_password: Option<String>,
+    /// 活动所在时区：数据库中的时间以 UTC 保存，只在显示、导出和解析管理员输入时按此时区转换
+    pub timezone: chrono::FixedOffset,
+}
+
+impl Config {
+    /// 默认配置：数据库位于当前目录，模板目录位于可执行文件同级的 template 文件夹
+    pub fn new(db_path: impl Into<PathBuf>) -> Self {
+        let exe_path = std::env::current_exe().unwrap_or_default();
+        let exe_dir = exe_path.parent().unwrap_or(std::path::Path::new(""));
+        Self {
+            db_path: db_path.into(),
+            template_dir: exe_dir.join("template"),
+            templates_from_disk: false,
+            trust_local_admin: false,
+            admin_origins: Vec::new(),
+            initial_admin_password: None,
+            timezone: chrono::FixedOffset::east_opt(utils::DEFAULT_UTC_OFFSET_SECS).expect("valid default offset"),
+        }
+    }
+
+    /// 从环境变量 `LANTERN_TIMEZONE` 读取活动时区 (如 `+08:00`)，未设置或格式错误时保持默认
+    pub fn timezone_from_env(&mut self) {
+        let value = match std::env::var("LANTERN_TIMEZONE") {
+            Ok(v) => v,
+            Err(_) => return,
+        };
+        match utils::parse_utc_offset(&value) {
+            Some(tz) => self.timezone = tz,
+            None => tracing::warn!("Invalid LANTERN_TIMEZONE '{}', using {}", value, self.timezone),
+        }
+    }
+
+    /// 从环境变量 `LANTERN_ADMIN_ORIGINS` 追加允许跨域访问的来源，多个来源用逗号分隔
+    pub fn admin_origins_from_env(&mut self) {
+        if let Ok(value) = std::env::var("LANTERN_ADMIN_ORIGINS") {
+            self.admin_origins.extend(value.split(',').map(|o| o.trim().trim_end_matches('/').to_string()).filter(|o| !o.is_empty()));
+        }
+    }
+}
+
+pub struct AppState {
+    pub db: sqlx::SqlitePool,
+    pub tera: Tera,
+    pub io: SocketIo,
+    pub session_key: auth::SessionKey,
+    pub config: Config,
+}
+
+impl AppState {
+    /// 连接数据库、初始化表结构并加载模板
+    pub async fn new(config: Config, io: SocketIo) -> Self {
+        let db = db::connect(&config.db_path)
+            .await
+            .expect("Failed to connect to database. Ensure you have write permissions.");
+        db::migrate(&db, config.timezone).await.expect("Failed to migrate database schema");
+        auth::ensure_default_admin(&db, config.initial_admin_password.as_deref())
+            .await
+            .expect("Failed to create default admin");
+        let session_key = auth::SessionKey::load_or_create(&db).await.expect("Failed to load session secret");
+
+        let tera = load_templates(&config);
+
+        Self { db, tera, io, session_key, config }
+    }
+}
+
+/// 构建完整的路由，包含 Socket.IO 层和 CORS
+pub async fn build_router(config: Config) -> Router {
+    let (layer, io) = SocketIo::new_layer();
+    let cors = cors_layer(&config);
+    let state = Arc::new(AppState::new(config, io.clone()).await);
+
+    events::register(&io, state.clone());
+    events::spawn_state_watcher(state.clone());
+    events::spawn_round_scheduler(state.clone());
+
+    // 需要管理员登录的接口，权限由 auth::require_admin 按请求方法判断
+    let admin_api = Router::new()
+        .route("/pro-api/me", get(handlers::admin::get_me))
+        .route("/pro-api/admins", get(handlers::admin::get_admins).post(handlers::admin::create_admin))
+        .route("/pro-api/admin/:id", axum::routing::delete(handlers::admin::delete_admin))
+        .route("/pro-api/users", get(handlers::admin::get_users))
+        .route("/pro-api/user/:id", axum::routing::delete(handlers::admin::delete_user))
+        .route("/pro-api/riddles", get(handlers::admin::get_riddles).post(handlers::admin::upsert_riddle))
+        .route("/pro-api/riddles/import", post(handlers::admin::import_riddles))
+        .route("/pro-api/riddles/import/suggest", post(handlers::admin::suggest_import_mapping))
+        .route("/pro-api/riddles/template", get(handlers::admin::download_import_template))
+        .route("/pro-api/riddles/mappings", get(handlers::admin::get_import_mappings).post(handlers::admin::save_import_mapping))
+        .route("/pro-api/riddles/mapping/:id", axum::routing::delete(handlers::admin::delete_import_mapping))
+        .route("/pro-api/riddles/facets", get(handlers::admin::get_riddle_facets))
+        .route("/pro-api/riddle/:id", axum::routing::delete(handlers::admin::delete_riddle))
+        .route("/pro-api/media", post(handlers::admin::upload_media))
+        .route("/pro-api/leaderboard", get(handlers::admin::get_leaderboard))
+        .route("/pro-api/stats/leaderboard", get(handlers::admin::get_ranking))
+        .route("/pro-api/records", get(handlers::admin::get_records))
+        .route("/pro-api/records/export", get(handlers::admin::export_records))
+        .route("/pro-api/activity", get(handlers::admin::get_activity).post(handlers::admin::update_activity))
+        .route("/pro-api/activities", get(handlers::admin::get_activities).post(handlers::admin::save_activity))
+        .route("/pro-api/activity/:id", axum::routing::delete(handlers::admin::delete_activity))
+        .route("/pro-api/activity/:id/activate", post(handlers::admin::activate_activity))
+        .route("/pro-api/activity/:id/pause", post(handlers::admin::pause_activity))
+        .route("/pro-api/activity/:id/resume", post(handlers::admin::resume_activity))
+        .route("/pro-api/activity/:id/extend", post(handlers::admin::extend_activity))
+        .route("/pro-api/activity/:id/open", post(handlers::admin::open_activity))
+        .route("/pro-api/activity/:id/close", post(handlers::admin::close_activity))
+        .route("/pro-api/rounds", get(handlers::admin::get_rounds).post(handlers::admin::save_round))
+        .route("/pro-api/round/:id", axum::routing::delete(handlers::admin::delete_round))
+        .route("/pro-api/round/:id/open", post(handlers::admin::open_round))
+        .route_layer(middleware::from_fn_with_state(state.clone(), auth::require_admin));
+
+    Router::new()
+        .route("/lantern", get(handlers::client::get_lantern))
+        .route("/frontend/index", get(handlers::client::get_frontend_index))
+        .route("/frontend/riddles", get(handlers::client::get_riddles))
+        .route("/frontend/riddles/facets", get(handlers::client::get_riddle_facets))
+        .route("/frontend/riddle/:id", get(handlers::client::get_riddle_by_id))
+        .route("/frontend/leaderboard", get(handlers::client::get_leaderboard))
+        .route("/q", get(handlers::client::get_q))
+        .route("/login", post(handlers::client::login))
+        .route("/logout", get(handlers::client::logout).post(handlers::client::logout))
+        .route("/guess", post(handlers::client::guess))
+        .route("/hints", get(handlers::client::get_hints).post(handlers::client::reveal_hint))
+        .route("/my/records", get(handlers::client::get_my_records))
+        .route("/pro-api/index", get(handlers::admin::get_admin_index))
+        .route("/pro-api/login", post(handlers::admin::login))
+        .merge(admin_api)
+        .fallback(static_handler)
+        .layer(axum::extract::DefaultBodyLimit::max(media::MAX_UPLOAD_BYTES))
+        .with_state(state)
+        .layer(layer)
+        .layer(cors)
+}
+
+/// 只允许配置的管理后台跨域访问，其他网页无法读取接口的响应
+fn cors_layer(config: &Config) -> CorsLayer {
+    let origins: Vec<_> = config.admin_origins.iter().filter_map(|o| o.parse().ok()).collect();
+    CorsLayer::new().allow_origin(AllowOrigin::list(origins)).allow_methods(Any).allow_headers(Any)
+}
+
+fn load_templates(config: &Config) -> Tera {
+    let mut tera = if config.templates_from_disk {
+        // 开发模式：从磁盘加载
+        let glob = config.template_dir.join("**").join("*.html");
+        Tera::new(&glob.to_string_lossy()).expect("Failed to load templates from disk")
+    } else {
+        // 生产模式：使用嵌入资源
+        let mut t = Tera::default();
+        let mut templates = Vec::new();
+        for file in Asset::iter() {
+            if file.ends_with(".html") {
+                if let Some(content) = Asset::get(&file) {
+                    let template_str = std::str::from_utf8(content.data.as_ref()).expect("UTF-8 error");
+                    templates.push((file.to_string(), template_str.to_string()));
+                }
+            }
+        }
+        t.add_raw_templates(templates).expect("Failed to load embedded templates");
+        t
+    };
+
+    // 模板中的活动时间为 UTC，按活动时区显示
+    let tz = config.timezone;
+    tera.register_filter("get_time_range_display", move |value: &serde_json::Value, _args: &std::collections::HashMap<String, serde_json::Value>| {
+        if value.is_null() {
+            return Ok(serde_json::Value::String("".to_string()));
+        }
+
+        let start_str = value.get("start_time").and_then(|v| v.as_str()).unwrap_or("");
+        let end_str = value.get("end_time").and_then(|v| v.as_str()).unwrap_or("");
+
+        if start_str.is_empty() || end_str.is_empty() {
+            return Ok(serde_json::Value::String("".to_string()));
+        }
+
+        // 尝试多种常见的日期格式进行解析
+        let parse_dt = |s: &str| {
+            chrono::NaiveDateTime::parse_from_str(s, "%Y-%m-%dT%H:%M:%S") // 带 T
+                .or_else(|_| chrono::NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S")) // 不带 T
+                .or_else(|_| chrono::NaiveDateTime::parse_from_str(s, "%Y-%m-%dT%H:%M:%S.%f")) // 带毫秒
+                .ok()
+        };
+
+        if let (Some(s), Some(e)) = (parse_dt(start_str), parse_dt(end_str)) {
+            Ok(serde_json::Value::String(utils::get_time_range_display(utils::to_local(s, tz), utils::to_local(e, tz))))
+        } else {
+            tracing::warn!("[Tera Filter Error] Failed to parse dates: start='{}', end='{}'", start_str, end_str);
+            Ok(serde_json::Value::String("".to_string()))
+        }
+    });
+
+    tera
+}
+
+async fn static_handler(State(state): State<Arc<AppState>>, uri: Uri) -> impl IntoResponse {
+    let path = uri.path().trim_start_matches('/');
+    let file_path = if path.is_empty() || path == "index.html" {
+        "index.html".to_string()
+    } else {
+        path.to_string()
+    };
+
+    // --- 统一逻辑：优先从本地磁盘读取 (支持上传的头像等动态资源) ---
+    let local_template_path = state.config.template_dir.join(&file_path);
+    if local_template_path.is_file() {
+        if let Ok(content) = std::fs::read(&local_template_path) {
+            let mime = mime_guess::from_path(&local_template_path).first_or_octet_stream();
+            return Response::builder()
+                .header(header::CONTENT_TYPE, mime.as_ref())
+                .body(Body::from(content))
+                .unwrap();
+        }
+    }
+
+    // --- 生产模式兜底：从嵌入资源中读取 ---
+    match Asset::get(&file_path) {
+        Some(content) => {
+            let mime = mime_guess::from_path(&file_path).first_or_octet_stream();
+            Response::builder()
+                .header(header::CONTENT_TYPE, mime.as_ref())
+                .body(Body::from(content.data))
+                .unwrap()
+        }
+        None => (StatusCode::NOT_FOUND, "404 Not Found").into_response(),
+    }
+}
+
+pub mod ax_extract {
+    use axum::{
+        async_trait,
+        extract::{FromRequest, Request},
+        Json, Form,
+        response::IntoResponse,
+    };
+    use serde::de::DeserializeOwned;
+
+    pub struct MaybeFormOrJson<T>(pub T);
+
+    #[async_trait]
+    impl<T, S> FromRequest<S> for MaybeFormOrJson<T>
+    where
+        T: DeserializeOwned,
+        S: Send + Sync,
+    {
+        type Rejection = axum::response::Response;
+
+        async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
+            let content_type = req
+                .headers()
+                .get(axum::http::header::CONTENT_TYPE)
+                .and_then(|value| value.to_str().ok())
+                .unwrap_or("");
+
+            if content_type.starts_with("application/json") {
+                let Json(data) = Json::<T>::from_request(req, state)
+                    .await
+                    .map_err(|e| e.into_response())?;
+                Ok(MaybeFormOrJson(data))
+            } else {
+                let Form(data) = Form::<T>::from_request(req, state)
+                    .await
+                    .map_err(|e| e.into_response())?;
+                Ok(MaybeFormOrJson(data))
+            }
+        }
+    }
+}
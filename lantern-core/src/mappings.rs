@@ -0,0 +1,64 @@
+use chrono::NaiveDateTime;
+use sqlx::SqlitePool;
+use crate::importer::{self, Column, Mapping};
+use crate::models::ImportMapping;
+
+/// 全部保存的列映射，最近保存的在前
+pub async fn list(pool: &SqlitePool) -> Vec<ImportMapping> {
+    sqlx::query_as("SELECT * FROM import_mappings ORDER BY updated_at DESC, id DESC")
+        .fetch_all(pool)
+        .await
+        .unwrap_or_default()
+}
+
+pub async fn find_by_name(pool: &SqlitePool, name: &str) -> Option<ImportMapping> {
+    sqlx::query_as("SELECT * FROM import_mappings WHERE name = ?")
+        .bind(name)
+        .fetch_optional(pool)
+        .await
+        .unwrap_or(None)
+}
+
+/// 按名称保存列映射，同名的覆盖；返回映射的 ID
+pub async fn save(pool: &SqlitePool, name: &str, mapping: &Mapping, now: NaiveDateTime) -> Result<i64, sqlx::Error> {
+    sqlx::query_scalar(
+        "INSERT INTO import_mappings (name, mapping_json, updated_at) VALUES (?, ?, ?)
+         ON CONFLICT(name) DO UPDATE SET mapping_json = excluded.mapping_json, updated_at = excluded.updated_at
+         RETURNING id",
+    )
+    .bind(name)
+    .bind(serde_json::to_string(mapping).unwrap_or_else(|_| "{}".to_string()))
+    .bind(now)
+    .fetch_one(pool)
+    .await
+}
+
+/// 删除列映射，返回 false 表示映射不存在
+pub async fn delete(pool: &SqlitePool, id: i64) -> bool {
+    sqlx::query("DELETE FROM import_mappings WHERE id = ?")
+        .bind(id)
+        .execute(pool)
+        .await
+        .map(|r| r.rows_affected() == 1)
+        .unwrap_or(false)
+}
+
+/// 解析保存的映射，格式错误时为空映射 (全部按表头识别)
+pub fn mapping(item: &ImportMapping) -> Mapping {
+    serde_json::from_str(&item.mapping_json).unwrap_or_default()
+}
+
+/// 与表头最吻合的已保存映射：覆盖的表头最多，且能找到灯谜题目与正确答案列；同样吻合时取最近保存的
+pub fn best_match<'a>(saved: &'a [ImportMapping], headers: &[String]) -> Option<&'a ImportMapping> {
+    let mut best: Option<(&ImportMapping, usize)> = None;
+    for item in saved {
+        let map = mapping(item);
+        let covered = headers.iter().filter(|h| map.contains_key(h.trim())).count();
+        let columns = importer::columns(headers, &map);
+        let complete = columns.contains(&Some(Column::Question)) && columns.contains(&Some(Column::Answer));
+        if covered > 0 && complete && best.is_none_or(|(_, n)| covered > n) {
+            best = Some((item, covered));
+        }
+    }
+    best.map(|(item, _)| item)
+}
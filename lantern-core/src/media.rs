@@ -0,0 +1,149 @@
+use std::collections::HashMap;
+use std::io::{Cursor, Read};
+use std::path::{Path, PathBuf};
+use sqlx::SqlitePool;
+use tokio::fs;
+use uuid::Uuid;
+use crate::{models::MediaType, sheet};
+
+/// 模板目录下保存灯谜媒体文件的子目录，由静态资源处理器以 `/media/...` 对外提供
+pub const MEDIA_DIR: &str = "media";
+/// 单个媒体文件的大小上限
+pub const MAX_MEDIA_BYTES: usize = 10 * 1024 * 1024;
+/// 上传请求体的大小上限；zip 包中的表格解压后同样不能超过
+pub const MAX_UPLOAD_BYTES: usize = 20 * 1024 * 1024;
+/// zip 包解压后的总大小上限，防止压缩炸弹耗尽内存
+pub const MAX_BUNDLE_BYTES: usize = 100 * 1024 * 1024;
+
+pub const IMAGE_EXTENSIONS: &[&str] = &["png", "jpg", "jpeg", "gif", "webp"];
+pub const AUDIO_EXTENSIONS: &[&str] = &["mp3", "m4a", "aac", "wav", "ogg"];
+
+fn extension(filename: &str) -> Option<String> {
+    Path::new(filename).extension().and_then(|s| s.to_str()).map(str::to_ascii_lowercase)
+}
+
+/// 按扩展名判断媒体类型，不支持的格式返回 `None`
+pub fn media_type_of(filename: &str) -> Option<MediaType> {
+    let ext = extension(filename)?;
+    if IMAGE_EXTENSIONS.contains(&ext.as_str()) {
+        Some(MediaType::Image)
+    } else if AUDIO_EXTENSIONS.contains(&ext.as_str()) {
+        Some(MediaType::Audio)
+    } else {
+        None
+    }
+}
+
+/// 媒体地址对应的本地文件，只接受 `/media/` 下不含 `..` 的地址
+pub fn local_path(template_dir: &Path, url: &str) -> Option<PathBuf> {
+    let relative = url.strip_prefix('/')?;
+    if !relative.starts_with(&format!("{}/", MEDIA_DIR)) || relative.split('/').any(|part| part == ".." || part.is_empty()) {
+        return None;
+    }
+    Some(template_dir.join(relative))
+}
+
+/// 保存上传的媒体文件，返回媒体类型与访问地址 `/media/YYYY/MM/DD/<uuid>.<ext>`
+pub async fn save(template_dir: &Path, date_dir: &str, filename: &str, data: &[u8]) -> Result<(MediaType, String), String> {
+    let media_type = media_type_of(filename)
+        .ok_or_else(|| format!("不支持的媒体格式: {}，图片支持 {}，音频支持 {}", filename, IMAGE_EXTENSIONS.join("/"), AUDIO_EXTENSIONS.join("/")))?;
+    if data.is_empty() {
+        return Err(format!("媒体文件为空: {}", filename));
+    }
+    if data.len() > MAX_MEDIA_BYTES {
+        return Err(format!("媒体文件超过 {} MB: {}", MAX_MEDIA_BYTES / 1024 / 1024, filename));
+    }
+
+    let upload_dir = template_dir.join(MEDIA_DIR).join(date_dir);
+    fs::create_dir_all(&upload_dir).await.map_err(|e| format!("创建媒体目录失败: {}", e))?;
+    let new_filename = format!("{}.{}", Uuid::new_v4().simple(), extension(filename).unwrap_or_default());
+    fs::write(upload_dir.join(&new_filename), data).await.map_err(|e| format!("保存媒体文件失败: {}", e))?;
+    Ok((media_type, format!("/{}/{}/{}", MEDIA_DIR, date_dir, new_filename)))
+}
+
+/// 删除不再被任何灯谜使用的媒体文件
+pub async fn remove_unused(pool: &SqlitePool, template_dir: &Path, urls: &[String]) {
+    for url in urls {
+        let in_use: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM riddles WHERE media_url = ?")
+            .bind(url)
+            .fetch_one(pool)
+            .await
+            .unwrap_or(1);
+        if in_use > 0 {
+            continue;
+        }
+        if let Some(path) = local_path(template_dir, url) {
+            if let Err(e) = fs::remove_file(&path).await {
+                tracing::warn!("Failed to remove media {}: {}", path.display(), e);
+            }
+        }
+    }
+}
+
+/// 导入用的 zip 包：一个表格 (xlsx、xls、ods、csv 或 json) 加上表格中引用的媒体文件
+pub struct Bundle {
+    /// 表格在包内的文件名，用来识别格式
+    pub sheet_name: String,
+    pub sheet: Vec<u8>,
+    files: HashMap<String, Vec<u8>>,
+}
+
+impl Bundle {
+    /// 按表格中填写的文件名查找媒体文件，先按包内完整路径匹配，再按文件名匹配
+    pub fn file(&self, name: &str) -> Option<&Vec<u8>> {
+        let name = name.trim().trim_start_matches("./").replace('\\', "/");
+        self.files.get(&name).or_else(|| {
+            let base = name.rsplit('/').next().unwrap_or(&name);
+            self.files.iter().find(|(k, _)| k.rsplit('/').next() == Some(base)).map(|(_, v)| v)
+        })
+    }
+}
+
+/// 解析上传的导入文件：不是 zip 包的原样返回 `None`，zip 包则取出其中第一个表格与媒体文件
+pub fn unpack(data: &[u8]) -> Result<Option<Bundle>, String> {
+    let mut archive = match zip::ZipArchive::new(Cursor::new(data)) {
+        Ok(a) => a,
+        Err(_) => return Ok(None),
+    };
+    // xlsx、ods 本身也是 zip，含有 [Content_Types].xml 或 mimetype 的就是表格文件
+    if archive.by_name("[Content_Types].xml").is_ok() || archive.by_name("mimetype").is_ok() {
+        return Ok(None);
+    }
+
+    let mut sheet = None;
+    let mut files = HashMap::new();
+    let mut total = 0;
+    for i in 0..archive.len() {
+        let mut entry = archive.by_index(i).map_err(|e| format!("读取压缩包失败: {}", e))?;
+        let name = entry.name().replace('\\', "/");
+        if entry.is_dir() || name.starts_with("__MACOSX/") || name.rsplit('/').next().is_some_and(|n| n.starts_with('.')) {
+            continue;
+        }
+        let is_sheet = extension(&name).is_some_and(|ext| sheet::EXTENSIONS.contains(&ext.as_str()));
+        if !is_sheet && media_type_of(&name).is_none() {
+            continue;
+        }
+        // 声明的大小可能不实，按上限多读一个字节来判断是否超出
+        let (limit, kind) = if is_sheet { (MAX_UPLOAD_BYTES, "表格") } else { (MAX_MEDIA_BYTES, "媒体") };
+        let too_large = || format!("{}文件超过 {} MB: {}", kind, limit / 1024 / 1024, name);
+        if entry.size() > limit as u64 {
+            return Err(too_large());
+        }
+        let mut buf = Vec::new();
+        (&mut entry).take(limit as u64 + 1).read_to_end(&mut buf).map_err(|e| format!("读取压缩包失败: {}", e))?;
+        if buf.len() > limit {
+            return Err(too_large());
+        }
+        total += buf.len();
+        if total > MAX_BUNDLE_BYTES {
+            return Err(format!("压缩包解压后超过 {} MB", MAX_BUNDLE_BYTES / 1024 / 1024));
+        }
+        if is_sheet {
+            sheet.get_or_insert((name, buf));
+        } else {
+            files.insert(name, buf);
+        }
+    }
+    let (sheet_name, sheet) = sheet.ok_or("压缩包中没有表格文件")?;
+    Ok(Some(Bundle { sheet_name, sheet, files }))
+}
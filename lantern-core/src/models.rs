@@ -0,0 +1,246 @@
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use chrono::NaiveDateTime;
+
+#[derive(Debug, Serialize, Deserialize, FromRow, Clone)]
+pub struct User {
+    pub id: i64,
+    pub username: String,
+    pub avatar: Option<String>,
+    pub user_code: Option<String>,
+    #[serde(skip_serializing)]
+    pub token: Option<String>,
+    pub register_time: Option<NaiveDateTime>,
+    #[serde(skip_serializing)]
+    #[sqlx(default)]
+    pub token_expires: Option<NaiveDateTime>,
+}
+
+#[derive(Debug, Serialize, Deserialize, FromRow, Clone)]
+pub struct Riddle {
+    pub id: i64,
+    pub activity_id: Option<i64>,
+    pub question: String,
+    pub remark: Option<String>,
+    pub options_json: String,
+    pub answer: String,
+    /// 备选答案 (JSON 字符串数组)，与 `answer` 同样判为答对
+    pub aliases_json: String,
+    pub match_mode: MatchMode,
+    /// 分值，猜中后乘以活动的难度系数，另加抢答加分
+    pub points: i64,
+    /// 难度等级，1 最简单
+    pub difficulty: i64,
+    /// 首次展示时间 (出现在大屏上或被玩家打开)，抢答加分从此开始计时
+    pub shown_at: Option<NaiveDateTime>,
+    pub add_time: Option<NaiveDateTime>,
+    pub is_solved: bool,
+    pub solver_id: Option<i64>,
+    /// 所属轮次，为空表示不分轮次、随时开放
+    pub round_id: Option<i64>,
+    /// 分类，如 `字谜`、`物谜`，空字符串表示未分类
+    pub category: String,
+    /// 标签 (JSON 字符串数组)
+    pub tags_json: String,
+    /// 逐条揭晓的提示 (JSON 数组，见 `hints::Hint`)
+    pub hints_json: String,
+    /// 谜面附带的图片或音频
+    pub media_type: MediaType,
+    /// 媒体文件地址，如 `/media/2026/02/12/<uuid>.png`，纯文字灯谜为空字符串
+    pub media_url: String,
+    /// 导入表格中的编号，按编号导入时用来找到已有的灯谜，空字符串表示没有编号
+    pub external_id: String,
+    /// 选项的展示顺序
+    pub option_order: OptionOrder,
+    /// 打乱选项用的种子，与灯谜、玩家一起决定玩家看到的顺序
+    pub option_seed: i64,
+}
+
+/// 保存的导入列映射，导入同一来源的表格时复用
+#[derive(Debug, Serialize, Deserialize, FromRow, Clone)]
+pub struct ImportMapping {
+    pub id: i64,
+    pub name: String,
+    /// 表头到列的映射，见 `importer::Mapping`
+    pub mapping_json: String,
+    pub updated_at: NaiveDateTime,
+}
+
+/// 轮次：活动中的一批灯谜，到达发布时间后才对玩家开放
+#[derive(Debug, Serialize, Deserialize, FromRow, Clone)]
+pub struct Round {
+    pub id: i64,
+    pub activity_id: i64,
+    pub name: String,
+    pub release_time: NaiveDateTime,
+    /// 已推送 `round_opened` 的时间，为空表示尚未推送
+    pub announced_at: Option<NaiveDateTime>,
+}
+
+#[derive(Debug, Serialize, Deserialize, FromRow, Clone)]
+pub struct Activity {
+    pub id: i64,
+    pub name: String,
+    pub start_time: NaiveDateTime,
+    pub end_time: NaiveDateTime,
+    /// 每位玩家每道灯谜最多作答次数，0 表示不限
+    pub max_attempts: i64,
+    /// 两次作答之间的最短间隔 (秒)
+    pub cooldown_secs: i64,
+    /// 各难度的积分系数 (JSON 数组)，`[1, 1.5, 2]` 依次对应难度 1~3，未配置的难度为 1
+    pub difficulty_multipliers_json: String,
+    /// 抢答加分上限，0 表示不加分
+    pub speed_bonus: i64,
+    /// 抢答加分的时间窗口 (秒)：灯谜展示后立即猜中得满分，之后线性递减到 0
+    pub speed_bonus_secs: i64,
+    /// 主持人暂停作答的时间，为空表示未暂停
+    pub paused_at: Option<NaiveDateTime>,
+}
+
+/// 一次作答记录：`is_correct` 表示答案正确，`is_solved` 表示答对并抢到了灯谜
+#[derive(Debug, Serialize, Deserialize, FromRow, Clone)]
+pub struct GuessRecord {
+    pub id: i64,
+    pub user_id: i64,
+    pub riddle_id: i64,
+    pub solve_time: Option<NaiveDateTime>,
+    pub is_solved: bool,
+    pub is_correct: bool,
+    pub answer: Option<String>,
+    pub normalized_answer: Option<String>,
+    /// 抢到灯谜获得的积分，未抢到为 0
+    pub points: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize, FromRow, Clone)]
+pub struct RiddleWithSolver {
+    pub id: i64,
+    pub activity_id: Option<i64>,
+    pub question: String,
+    pub remark: Option<String>,
+    pub options_json: String,
+    pub answer: String,
+    pub aliases_json: String,
+    pub match_mode: MatchMode,
+    pub points: i64,
+    pub difficulty: i64,
+    pub shown_at: Option<NaiveDateTime>,
+    pub add_time: Option<NaiveDateTime>,
+    pub is_solved: bool,
+    pub solver_id: Option<i64>,
+    pub round_id: Option<i64>,
+    pub category: String,
+    pub tags_json: String,
+    pub hints_json: String,
+    pub media_type: MediaType,
+    pub media_url: String,
+    pub external_id: String,
+    pub option_order: OptionOrder,
+    pub option_seed: i64,
+    pub solver_name: Option<String>,
+    pub solver_avatar: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, FromRow, Clone)]
+pub struct GuessRecordWithInfo {
+    pub id: i64,
+    pub user_id: i64,
+    #[sqlx(default)]
+    pub user_name: Option<String>,
+    pub riddle_id: i64,
+    pub riddle_question: Option<String>,
+    pub riddle_answer: Option<String>,
+    pub solve_time: Option<NaiveDateTime>,
+    pub is_solved: bool,
+    pub is_correct: bool,
+    /// 玩家提交的原始答案
+    pub answer: Option<String>,
+    /// 判定时使用的归一化答案，见 [`crate::answer::normalize`]
+    pub normalized_answer: Option<String>,
+    pub points: i64,
+}
+
+/// 排行榜中的一名玩家：按总积分、猜中数排名，仍然相同时最后一次猜中更早的在前
+#[derive(Debug, Serialize, Deserialize, FromRow, Clone)]
+pub struct RankEntry {
+    pub rank: i64,
+    pub activity_id: Option<i64>,
+    pub user_id: i64,
+    pub username: String,
+    pub avatar: Option<String>,
+    pub score: i64,
+    pub solved_count: i64,
+    pub last_solve_time: Option<NaiveDateTime>,
+}
+
+/// 灯谜的答案匹配方式，均在忽略空白、标点、全半角的基础上判断
+#[derive(Debug, Serialize, Deserialize, sqlx::Type, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(rename_all = "snake_case")]
+pub enum MatchMode {
+    /// 与谜底或备选答案一致
+    #[default]
+    Normal,
+    /// 另外接受繁体字作答 (转换为简体后比较)
+    Traditional,
+    /// 另外接受繁体字或不带声调的拼音作答，如 `beijing`
+    Pinyin,
+}
+
+/// 选择题选项的展示顺序
+#[derive(Debug, Serialize, Deserialize, sqlx::Type, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(rename_all = "snake_case")]
+pub enum OptionOrder {
+    /// 按录入的顺序
+    #[default]
+    Keep,
+    /// 每个玩家看到的顺序不同 (按种子确定，同一玩家每次相同)
+    Shuffle,
+}
+
+/// 谜面附带的媒体类型
+#[derive(Debug, Serialize, Deserialize, sqlx::Type, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(rename_all = "snake_case")]
+pub enum MediaType {
+    /// 纯文字灯谜
+    #[default]
+    None,
+    Image,
+    Audio,
+}
+
+/// 活动状态，由当前时间、活动的起止时间与是否暂停决定
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ActivityState {
+    /// 尚未开始
+    Scheduled,
+    /// 进行中，可以作答
+    Running,
+    /// 主持人暂停了作答
+    Paused,
+    /// 已结束
+    Ended,
+}
+
+#[derive(Debug, Serialize, Deserialize, sqlx::Type, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(rename_all = "snake_case")]
+pub enum AdminRole {
+    /// 只能查看与导出
+    Operator,
+    /// 可删除用户、编辑灯谜与活动、管理管理员账号
+    SuperAdmin,
+}
+
+#[derive(Debug, Serialize, Deserialize, FromRow, Clone)]
+pub struct Admin {
+    pub id: i64,
+    pub username: String,
+    #[serde(skip_serializing)]
+    pub password_hash: String,
+    pub role: AdminRole,
+    pub create_time: Option<NaiveDateTime>,
+}
@@ -0,0 +1,41 @@
+use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
+use crate::{answer, models::OptionOrder};
+
+/// [`with_answer`] 补上正确答案的规则，导入模板与文档据此说明
+pub const MISSING_ANSWER_RULE: &str = "有选项但不含正确答案时自动加入：打乱顺序时加到末尾，按录入顺序时按种子插入到固定位置，不总是最后一项";
+
+/// 整理选择题的选项：去掉空白选项；有选项时保证正确答案在其中
+///
+/// 缺少正确答案时：`Shuffle` 追加到末尾 (玩家看到的顺序本就打乱)；`Keep` 按种子与答案插入到确定的位置，避免答案总是最后一项。
+/// 返回整理后的选项，以及是否加入了正确答案
+pub fn with_answer(answer: &str, options: Vec<String>, order: OptionOrder, seed: i64) -> (Vec<String>, bool) {
+    let mut options: Vec<String> = options.into_iter().map(|o| o.trim().to_string()).filter(|o| !o.is_empty()).collect();
+    let correct = answer::normalize(answer);
+    let missing = !options.is_empty() && !correct.is_empty() && !options.iter().any(|o| answer::normalize(o) == correct);
+    if missing {
+        let at = match order {
+            OptionOrder::Shuffle => options.len(),
+            OptionOrder::Keep => answer_slot(&correct, options.len(), seed),
+        };
+        options.insert(at, answer.trim().to_string());
+    }
+    (options, missing)
+}
+
+/// 答案在 `len` 个选项中的插入位置 (0..=len)，同一种子与答案每次相同
+fn answer_slot(correct: &str, len: usize, seed: i64) -> usize {
+    // FNV-1a，不随 Rust 版本变化
+    let hash = correct.bytes().fold(0xCBF2_9CE4_8422_2325u64, |h, b| (h ^ b as u64).wrapping_mul(0x0100_0000_01B3));
+    StdRng::seed_from_u64(seed as u64 ^ hash).gen_range(0..=len)
+}
+
+/// 玩家看到的选项顺序：`Keep` 保持录入顺序；`Shuffle` 按种子、灯谜与玩家确定地打乱，同一玩家每次打开看到的顺序相同
+pub fn for_player(mut options: Vec<String>, order: OptionOrder, seed: i64, riddle_id: i64, user_id: i64) -> Vec<String> {
+    if order == OptionOrder::Shuffle {
+        let mixed = (seed as u64)
+            ^ (riddle_id as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15)
+            ^ (user_id as u64).wrapping_mul(0xC2B2_AE3D_27D4_EB4F);
+        options.shuffle(&mut StdRng::seed_from_u64(mixed));
+    }
+    options
+}
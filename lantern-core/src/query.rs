@@ -0,0 +1,221 @@
+use chrono::{FixedOffset, NaiveDate, NaiveDateTime};
+use crate::utils::to_utc;
+use serde::Serialize;
+use sqlx::{sqlite::SqliteRow, FromRow, QueryBuilder, Sqlite, SqlitePool};
+
+/// 单页最大条数，防止一次性拉取过多数据
+pub const MAX_PAGE_SIZE: u32 = 500;
+
+/// 绑定到 SQL 中的参数值，所有用户输入都通过它传入，不会拼接进 SQL 字符串
+#[derive(Debug, Clone)]
+pub enum Arg {
+    Int(i64),
+    Bool(bool),
+    Text(String),
+    DateTime(NaiveDateTime),
+}
+
+/// 分页列表的统一响应结构 `{ total, page, totalPages, list }`
+#[derive(Debug, Serialize)]
+pub struct Page<T> {
+    pub total: i64,
+    pub page: u32,
+    #[serde(rename = "totalPages")]
+    pub total_pages: i64,
+    pub list: Vec<T>,
+}
+
+impl<T> Page<T> {
+    pub fn map<U>(self, f: impl FnMut(T) -> U) -> Page<U> {
+        Page {
+            total: self.total,
+            page: self.page,
+            total_pages: self.total_pages,
+            list: self.list.into_iter().map(f).collect(),
+        }
+    }
+}
+
+/// 列表查询构建器：`SELECT {select} FROM {from} WHERE ... ORDER BY ... LIMIT ? OFFSET ?`
+///
+/// ```ignore
+/// let page = ListQuery::new("r.*", "riddles r")
+///     .keyword(&["r.question"], params.keyword.as_deref())
+///     .eq("r.is_solved", parse_bool_param(params.solved.as_deref()).map(Arg::Bool))
+///     .order_by(params.sort.as_deref(), params.order.as_deref(), &[("add_time", "r.add_time")], "r.add_time")
+///     .fetch_page::<Riddle>(&pool, params.page, params.page_size)
+///     .await?;
+/// ```
+pub struct ListQuery {
+    select: String,
+    from: String,
+    conditions: Vec<(String, Vec<Arg>)>,
+    order: Option<String>,
+}
+
+impl ListQuery {
+    pub fn new(select: &str, from: &str) -> Self {
+        Self {
+            select: select.to_string(),
+            from: from.to_string(),
+            conditions: Vec::new(),
+            order: None,
+        }
+    }
+
+    /// 追加一个固定条件，`sql` 中的每个 `?` 依次绑定 `args`
+    pub fn filter(mut self, sql: &str, args: Vec<Arg>) -> Self {
+        debug_assert_eq!(sql.matches('?').count(), args.len());
+        self.conditions.push((sql.to_string(), args));
+        self
+    }
+
+    /// 只有一个参数的条件，值为 `None` 时忽略
+    pub fn filter_opt(self, sql: &str, value: Option<Arg>) -> Self {
+        match value {
+            Some(v) => self.filter(sql, vec![v]),
+            None => self,
+        }
+    }
+
+    /// 等值过滤，值为 `None` 时忽略
+    pub fn eq(self, column: &str, value: Option<Arg>) -> Self {
+        self.filter_opt(&format!("{} = ?", column), value)
+    }
+
+    /// 关键字模糊匹配任意一列，`%` 与 `_` 按字面匹配
+    pub fn keyword(self, columns: &[&str], keyword: Option<&str>) -> Self {
+        let keyword = keyword.map(str::trim).unwrap_or_default();
+        if keyword.is_empty() || columns.is_empty() {
+            return self;
+        }
+        let pattern = format!("%{}%", escape_like(keyword));
+        let sql = columns
+            .iter()
+            .map(|c| format!("{} LIKE ? ESCAPE '\\'", c))
+            .collect::<Vec<_>>()
+            .join(" OR ");
+        let args = columns.iter().map(|_| Arg::Text(pattern.clone())).collect();
+        self.filter(&format!("({})", sql), args)
+    }
+
+    /// 时间范围过滤 (闭区间)，任一端为 `None` 时不限制
+    pub fn date_range(self, column: &str, from: Option<NaiveDateTime>, to: Option<NaiveDateTime>) -> Self {
+        let query = match from {
+            Some(f) => self.filter(&format!("{} >= ?", column), vec![Arg::DateTime(f)]),
+            None => self,
+        };
+        match to {
+            Some(t) => query.filter(&format!("{} <= ?", column), vec![Arg::DateTime(t)]),
+            None => query,
+        }
+    }
+
+    /// 排序：`sort` 必须是 `allowed` 中的字段名，否则使用 `default`
+    pub fn order_by(mut self, sort: Option<&str>, order: Option<&str>, allowed: &[(&str, &str)], default: &str) -> Self {
+        let column = sort
+            .and_then(|s| allowed.iter().find(|(name, _)| *name == s))
+            .map(|(_, column)| *column)
+            .unwrap_or(default);
+        let direction = match order.map(|o| o.to_ascii_lowercase()) {
+            Some(o) if o == "asc" || o == "ascending" => "ASC",
+            _ => "DESC",
+        };
+        self.order = Some(format!("{} {}", column, direction));
+        self
+    }
+
+    fn builder(&self, select: &str) -> QueryBuilder<'_, Sqlite> {
+        let mut qb = QueryBuilder::new(format!("SELECT {} FROM {}", select, self.from));
+        for (i, (sql, args)) in self.conditions.iter().enumerate() {
+            qb.push(if i == 0 { " WHERE " } else { " AND " });
+            let mut parts = sql.split('?');
+            qb.push(parts.next().unwrap_or_default());
+            for (arg, part) in args.iter().zip(parts) {
+                match arg {
+                    Arg::Int(v) => qb.push_bind(*v),
+                    Arg::Bool(v) => qb.push_bind(*v),
+                    Arg::Text(v) => qb.push_bind(v.clone()),
+                    Arg::DateTime(v) => qb.push_bind(*v),
+                };
+                qb.push(part);
+            }
+        }
+        qb
+    }
+
+    pub async fn count(&self, pool: &SqlitePool) -> Result<i64, sqlx::Error> {
+        let (total,): (i64,) = self.builder("COUNT(*)").build_query_as().fetch_one(pool).await?;
+        Ok(total)
+    }
+
+    /// 不分页，查询全部符合条件的记录 (用于导出)
+    pub async fn fetch_all<T>(&self, pool: &SqlitePool) -> Result<Vec<T>, sqlx::Error>
+    where
+        T: for<'r> FromRow<'r, SqliteRow> + Send + Unpin,
+    {
+        let mut qb = self.builder(&self.select);
+        if let Some(order) = &self.order {
+            qb.push(" ORDER BY ").push(order);
+        }
+        qb.build_query_as().fetch_all(pool).await
+    }
+
+    /// 分页查询，`page` 从 1 开始
+    pub async fn fetch_page<T>(&self, pool: &SqlitePool, page: Option<u32>, page_size: Option<u32>) -> Result<Page<T>, sqlx::Error>
+    where
+        T: for<'r> FromRow<'r, SqliteRow> + Send + Unpin,
+    {
+        let page = page.unwrap_or(1).max(1);
+        let page_size = page_size.unwrap_or(10).clamp(1, MAX_PAGE_SIZE);
+        let offset = (page - 1) as i64 * page_size as i64;
+
+        let total = self.count(pool).await?;
+
+        let mut qb = self.builder(&self.select);
+        if let Some(order) = &self.order {
+            qb.push(" ORDER BY ").push(order);
+        }
+        qb.push(" LIMIT ").push_bind(page_size as i64).push(" OFFSET ").push_bind(offset);
+        let list = qb.build_query_as().fetch_all(pool).await?;
+
+        Ok(Page {
+            total,
+            page,
+            total_pages: (total as f64 / page_size as f64).ceil() as i64,
+            list,
+        })
+    }
+}
+
+fn escape_like(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_")
+}
+
+/// 解析查询参数中的日期时间，支持 `YYYY-MM-DD HH:MM:SS`、`YYYY-MM-DDTHH:MM:SS` 和 `YYYY-MM-DD`
+///
+/// 参数按活动时区 `tz` 理解，返回 UTC 时间用于查询；仅有日期时，`end_of_day` 为 true 则取当天 23:59:59，否则取 00:00:00
+pub fn parse_datetime_param(value: Option<&str>, end_of_day: bool, tz: FixedOffset) -> Option<NaiveDateTime> {
+    let value = value.map(str::trim).filter(|v| !v.is_empty())?;
+    let local = NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S")
+        .or_else(|_| NaiveDateTime::parse_from_str(value, "%Y-%m-%dT%H:%M:%S"))
+        .ok()
+        .or_else(|| {
+            let date = NaiveDate::parse_from_str(value, "%Y-%m-%d").ok()?;
+            if end_of_day {
+                date.and_hms_opt(23, 59, 59)
+            } else {
+                date.and_hms_opt(0, 0, 0)
+            }
+        })?;
+    Some(to_utc(local, tz))
+}
+
+/// 解析查询参数中的布尔值，支持 `1/0`、`true/false`，空值或无法识别时返回 `None`
+pub fn parse_bool_param(value: Option<&str>) -> Option<bool> {
+    match value.map(|v| v.trim().to_ascii_lowercase()).as_deref() {
+        Some("1") | Some("true") | Some("yes") => Some(true),
+        Some("0") | Some("false") | Some("no") => Some(false),
+        _ => None,
+    }
+}
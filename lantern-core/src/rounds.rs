@@ -0,0 +1,71 @@
+use chrono::NaiveDateTime;
+use sqlx::SqlitePool;
+use crate::models::Round;
+
+/// 灯谜已开放的条件 (`r` 为 riddles 表的别名)：不分轮次，或所属轮次已到发布时间；需绑定当前时间
+pub const RELEASED: &str = "(r.round_id IS NULL OR r.round_id IN (SELECT id FROM rounds WHERE release_time <= ?))";
+
+pub async fn find(pool: &SqlitePool, id: i64) -> Option<Round> {
+    sqlx::query_as("SELECT * FROM rounds WHERE id = ?")
+        .bind(id)
+        .fetch_optional(pool)
+        .await
+        .unwrap_or(None)
+}
+
+/// 活动的全部轮次，按发布时间排序
+pub async fn list(pool: &SqlitePool, activity_id: i64) -> Vec<Round> {
+    sqlx::query_as("SELECT * FROM rounds WHERE activity_id = ? ORDER BY release_time, id")
+        .bind(activity_id)
+        .fetch_all(pool)
+        .await
+        .unwrap_or_default()
+}
+
+/// 灯谜所属的轮次在 `now` 时是否已开放，不分轮次的灯谜始终开放
+pub async fn is_released(pool: &SqlitePool, round_id: Option<i64>, now: NaiveDateTime) -> bool {
+    match round_id {
+        Some(id) => find(pool, id).await.map(|r| r.release_time <= now).unwrap_or(true),
+        None => true,
+    }
+}
+
+pub async fn riddle_count(pool: &SqlitePool, id: i64) -> i64 {
+    sqlx::query_scalar("SELECT COUNT(*) FROM riddles WHERE round_id = ?")
+        .bind(id)
+        .fetch_one(pool)
+        .await
+        .unwrap_or(0)
+}
+
+/// 已到发布时间但还没有推送过的轮次
+pub async fn due(pool: &SqlitePool, now: NaiveDateTime) -> Vec<Round> {
+    sqlx::query_as("SELECT * FROM rounds WHERE release_time <= ? AND announced_at IS NULL ORDER BY release_time, id")
+        .bind(now)
+        .fetch_all(pool)
+        .await
+        .unwrap_or_default()
+}
+
+/// 标记轮次已推送，返回 false 表示已被标记过
+pub async fn mark_announced(pool: &SqlitePool, id: i64, now: NaiveDateTime) -> bool {
+    sqlx::query("UPDATE rounds SET announced_at = ? WHERE id = ? AND announced_at IS NULL")
+        .bind(now)
+        .bind(id)
+        .execute(pool)
+        .await
+        .map(|r| r.rows_affected() == 1)
+        .unwrap_or(false)
+}
+
+/// 轮次中尚未被猜中的灯谜
+pub async fn unsolved_riddle_ids(pool: &SqlitePool, id: i64) -> Vec<i64> {
+    sqlx::query_as::<_, (i64,)>("SELECT id FROM riddles WHERE round_id = ? AND is_solved = 0 ORDER BY id")
+        .bind(id)
+        .fetch_all(pool)
+        .await
+        .unwrap_or_default()
+        .into_iter()
+        .map(|(id,)| id)
+        .collect()
+}
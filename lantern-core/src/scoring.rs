@@ -0,0 +1,116 @@
+use chrono::{FixedOffset, NaiveDateTime};
+use sqlx::{QueryBuilder, Sqlite, SqlitePool};
+use serde_json::json;
+use crate::models::{Activity, RankEntry};
+use crate::query::{Arg, ListQuery};
+use crate::utils::format_local;
+
+/// 大屏排行榜默认显示的人数
+pub const DEFAULT_LEADERBOARD_SIZE: u32 = 10;
+
+/// 新建灯谜的默认分值
+pub const DEFAULT_POINTS: i64 = 10;
+
+/// 难度等级范围，1 最简单
+pub const MIN_DIFFICULTY: i64 = 1;
+pub const MAX_DIFFICULTY: i64 = 5;
+
+/// 解析活动的难度系数，格式错误时视为未配置
+pub fn parse_multipliers(json: &str) -> Vec<f64> {
+    serde_json::from_str(json).unwrap_or_default()
+}
+
+/// 只保留有效的系数 (非负数)，用于保存活动设置
+pub fn clean_multipliers(multipliers: Vec<f64>) -> Vec<f64> {
+    multipliers.into_iter().map(|m| if m.is_finite() && m >= 0.0 { m } else { 1.0 }).collect()
+}
+
+/// 某个难度的积分系数，未配置时为 1
+pub fn multiplier(multipliers: &[f64], difficulty: i64) -> f64 {
+    usize::try_from(difficulty - MIN_DIFFICULTY)
+        .ok()
+        .and_then(|i| multipliers.get(i))
+        .copied()
+        .filter(|m| m.is_finite() && *m >= 0.0)
+        .unwrap_or(1.0)
+}
+
+/// 抢答加分：灯谜展示后立即猜中得 `max_bonus`，在 `window_secs` 内线性递减到 0
+pub fn speed_bonus(max_bonus: i64, window_secs: i64, elapsed_secs: i64) -> i64 {
+    if max_bonus <= 0 || window_secs <= 0 || elapsed_secs >= window_secs {
+        return 0;
+    }
+    let remaining = (window_secs - elapsed_secs.max(0)) as f64 / window_secs as f64;
+    (max_bonus as f64 * remaining).round() as i64
+}
+
+/// 猜中一道灯谜获得的积分 = 分值 × 难度系数 + 抢答加分
+///
+/// 不属于任何活动的灯谜只计分值；从未展示过的灯谜没有抢答加分
+pub fn points_for(points: i64, difficulty: i64, shown_at: Option<NaiveDateTime>, activity: Option<&Activity>, solve_time: NaiveDateTime) -> i64 {
+    let points = points.max(0);
+    let act = match activity {
+        Some(a) => a,
+        None => return points,
+    };
+    let base = (points as f64 * multiplier(&parse_multipliers(&act.difficulty_multipliers_json), difficulty)).round() as i64;
+    let bonus = shown_at
+        .map(|t| speed_bonus(act.speed_bonus, act.speed_bonus_secs, (solve_time - t).num_seconds()))
+        .unwrap_or(0);
+    base + bonus
+}
+
+/// 记录灯谜的首次展示时间，已记录过的不会被覆盖
+pub async fn mark_shown(pool: &SqlitePool, riddle_ids: &[i64], time: NaiveDateTime) {
+    if riddle_ids.is_empty() {
+        return;
+    }
+    let mut qb: QueryBuilder<Sqlite> = QueryBuilder::new("UPDATE riddles SET shown_at = ");
+    qb.push_bind(time).push(" WHERE shown_at IS NULL AND id IN (");
+    let mut ids = qb.separated(", ");
+    for id in riddle_ids {
+        ids.push_bind(*id);
+    }
+    qb.push(")");
+    let _ = qb.build().execute(pool).await;
+}
+
+/// 排行榜：按活动汇总每位玩家抢到的灯谜，排名在过滤关键字之前计算
+pub fn ranking_query(activity_id: Option<i64>, keyword: Option<&str>) -> ListQuery {
+    ListQuery::new(
+        "rk.*",
+        "(SELECT s.*, RANK() OVER (PARTITION BY s.activity_id ORDER BY s.score DESC, s.solved_count DESC, s.last_solve_time ASC) AS rank
+          FROM (SELECT r.activity_id, gr.user_id, u.username, u.avatar,
+                       SUM(gr.points) AS score, COUNT(*) AS solved_count, MAX(gr.solve_time) AS last_solve_time
+                FROM guess_records gr
+                JOIN riddles r ON gr.riddle_id = r.id
+                JOIN users u ON gr.user_id = u.id
+                WHERE gr.is_solved = 1
+                GROUP BY r.activity_id, gr.user_id) s) rk",
+    )
+    .eq("rk.activity_id", activity_id.map(Arg::Int))
+    .keyword(&["rk.username"], keyword)
+    .order_by(None, Some("asc"), &[], "rk.rank")
+}
+
+/// 排行榜条目的 JSON 表示，时间按活动时区格式化为 `YYYY-MM-DD HH:MM:SS`
+pub fn rank_json(entry: &RankEntry, tz: FixedOffset) -> serde_json::Value {
+    let mut val = json!(entry);
+    if let Some(t) = entry.last_solve_time {
+        val["last_solve_time"] = json!(format_local(t, tz));
+    }
+    val
+}
+
+/// 活动排行榜的前 `limit` 名：`{ activity_id, activity_name, total, list }`，供大屏接口与实时推送共用
+pub async fn leaderboard(pool: &SqlitePool, activity: &Activity, limit: u32, tz: FixedOffset) -> Result<serde_json::Value, sqlx::Error> {
+    let page = ranking_query(Some(activity.id), None)
+        .fetch_page::<RankEntry>(pool, Some(1), Some(limit))
+        .await?;
+    Ok(json!({
+        "activity_id": activity.id,
+        "activity_name": activity.name,
+        "total": page.total,
+        "list": page.list.iter().map(|entry| rank_json(entry, tz)).collect::<Vec<_>>(),
+    }))
+}
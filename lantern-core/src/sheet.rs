@@ -0,0 +1,307 @@
+use std::io::{Cursor, Write};
+use std::path::Path;
+use calamine::{open_workbook_auto_from_rs, Reader};
+use encoding_rs::{Encoding, GB18030};
+use serde_json::Value;
+use zip::write::FileOptions;
+
+/// 可以导入的表格文件扩展名，zip 包中按这些扩展名查找表格
+pub const EXTENSIONS: &[&str] = &["xlsx", "xls", "ods", "csv", "json"];
+
+/// 上传的表格统一读成表头加数据行，各种格式共用同一套列识别
+#[derive(Debug, Default)]
+pub struct Table {
+    pub headers: Vec<String>,
+    pub rows: Vec<Vec<String>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Format {
+    /// xlsx / xls / ods，由 calamine 按内容识别
+    Workbook,
+    Csv,
+    Json,
+}
+
+fn detect(filename: &str, data: &[u8]) -> Format {
+    // zip (xlsx、ods) 与 OLE (xls) 文件头
+    if data.starts_with(b"PK\x03\x04") || data.starts_with(&[0xD0, 0xCF, 0x11, 0xE0]) {
+        return Format::Workbook;
+    }
+    match Path::new(filename).extension().and_then(|s| s.to_str()).map(str::to_ascii_lowercase).as_deref() {
+        Some("json") => Format::Json,
+        Some("csv") => Format::Csv,
+        _ if decode(data).trim_start().starts_with('[') => Format::Json,
+        _ => Format::Csv,
+    }
+}
+
+/// 按内容与文件名识别格式并读出第一个工作表
+pub fn read(filename: &str, data: Vec<u8>) -> Result<Table, String> {
+    match detect(filename, &data) {
+        Format::Workbook => read_workbook(data),
+        Format::Csv => Ok(read_csv(&decode(&data))),
+        Format::Json => read_json(&decode(&data)),
+    }
+}
+
+fn read_workbook(data: Vec<u8>) -> Result<Table, String> {
+    let mut workbook = open_workbook_auto_from_rs(Cursor::new(data)).map_err(|e| format!("无法识别的表格文件: {}", e))?;
+    let range = match workbook.worksheet_range_at(0) {
+        Some(Ok(range)) => range,
+        Some(Err(e)) => return Err(format!("读取工作表失败: {}", e)),
+        None => return Err("表格中没有工作表".to_string()),
+    };
+    let mut rows = range.rows().map(|r| r.iter().map(|c| c.to_string()).collect::<Vec<String>>());
+    let headers = rows.next().unwrap_or_default();
+    Ok(Table { headers, rows: rows.collect() })
+}
+
+/// 文本文件的编码：带 BOM 的按 BOM，合法的 UTF-8 按 UTF-8，否则按 GBK (WPS、Excel 中文版默认导出的编码)
+fn decode(data: &[u8]) -> String {
+    if let Some((encoding, bom_len)) = Encoding::for_bom(data) {
+        return encoding.decode_without_bom_handling(&data[bom_len..]).0.into_owned();
+    }
+    match std::str::from_utf8(data) {
+        Ok(text) => text.to_string(),
+        Err(_) => GB18030.decode_without_bom_handling(data).0.into_owned(),
+    }
+}
+
+/// 按 RFC 4180 解析 CSV：支持引号内的逗号、换行与 `""` 转义；表头中制表符多于逗号时按制表符分隔
+fn read_csv(text: &str) -> Table {
+    let first_line = text.lines().next().unwrap_or_default();
+    let delimiter = if first_line.matches('\t').count() > first_line.matches(',').count() { '\t' } else { ',' };
+
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    let mut cell = String::new();
+    let mut quoted = false;
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                cell.push('"');
+                chars.next();
+            }
+            '"' if quoted => quoted = false,
+            '"' if cell.is_empty() => quoted = true,
+            c if c == delimiter && !quoted => row.push(std::mem::take(&mut cell)),
+            '\r' if !quoted => {}
+            '\n' if !quoted => {
+                row.push(std::mem::take(&mut cell));
+                rows.push(std::mem::take(&mut row));
+            }
+            c => cell.push(c),
+        }
+    }
+    if !cell.is_empty() || !row.is_empty() {
+        row.push(cell);
+        rows.push(row);
+    }
+
+    let mut rows = rows.into_iter();
+    let headers = rows.next().unwrap_or_default();
+    Table { headers, rows: rows.collect() }
+}
+
+/// JSON 单元格的文本：数组按元素拆成多列，提示等对象取 `text`
+fn cell_text(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(s) => s.clone(),
+        Value::Object(map) => map.get("text").map(cell_text).unwrap_or_else(|| value.to_string()),
+        other => other.to_string(),
+    }
+}
+
+fn cell_values(key: &str, value: &Value) -> (String, Vec<String>) {
+    // 与 `Riddle` 一致的 `options_json` 等字段是 JSON 字符串，按数组处理
+    if let (Some(name), Value::String(s)) = (key.strip_suffix("_json"), value) {
+        if let Ok(Value::Array(items)) = serde_json::from_str::<Value>(s) {
+            return (name.to_string(), items.iter().map(cell_text).collect());
+        }
+    }
+    match value {
+        Value::Array(items) => (key.to_string(), items.iter().map(cell_text).collect()),
+        other => (key.to_string(), vec![cell_text(other)]),
+    }
+}
+
+/// JSON 数组，每个元素是一道灯谜，字段名与 `Riddle` 相同 (也可以用中文表头)，数组字段拆成多列
+fn read_json(text: &str) -> Result<Table, String> {
+    let items: Vec<serde_json::Map<String, Value>> = serde_json::from_str(text).map_err(|e| format!("JSON 格式错误，需要灯谜对象的数组: {}", e))?;
+    let items: Vec<Vec<(String, Vec<String>)>> = items.iter().map(|item| item.iter().map(|(k, v)| cell_values(k, v)).collect()).collect();
+
+    // 每个字段占的列数取所有灯谜中最多的
+    let mut widths: Vec<(String, usize)> = Vec::new();
+    for (key, values) in items.iter().flatten() {
+        match widths.iter_mut().find(|(k, _)| k == key) {
+            Some((_, width)) => *width = (*width).max(values.len()),
+            None => widths.push((key.clone(), values.len().max(1))),
+        }
+    }
+
+    let headers = widths.iter().flat_map(|(key, width)| std::iter::repeat_n(key.clone(), *width)).collect();
+    let rows = items
+        .into_iter()
+        .map(|fields| {
+            widths
+                .iter()
+                .flat_map(|(key, width)| {
+                    let values = fields.iter().find(|(k, _)| k == key).map(|(_, v)| v.clone()).unwrap_or_default();
+                    (0..*width).map(move |i| values.get(i).cloned().unwrap_or_default())
+                })
+                .collect()
+        })
+        .collect();
+    Ok(Table { headers, rows })
+}
+
+/// 写入 xlsx 的单元格
+#[derive(Debug, Clone)]
+pub enum Cell {
+    Text(String),
+    Number(f64),
+}
+
+/// 单元格的数据有效性规则
+#[derive(Debug, Clone)]
+pub enum Rule {
+    /// 只显示输入提示
+    Any,
+    /// 从下拉列表中选择
+    List(Vec<String>),
+    /// 不小于给定值的整数
+    WholeAtLeast(i64),
+}
+
+/// 一列的数据有效性，作用于表头以下的单元格；选中单元格时显示 `prompt`
+#[derive(Debug, Clone)]
+pub struct Validation {
+    pub column: usize,
+    pub rule: Rule,
+    /// Excel 限制标题不超过 32 个字符、提示不超过 255 个字符
+    pub title: String,
+    pub prompt: String,
+}
+
+/// 写入 xlsx 的工作表，第一行为表头 (加粗并冻结)
+#[derive(Debug, Clone, Default)]
+pub struct Worksheet {
+    pub name: String,
+    pub rows: Vec<Vec<Cell>>,
+    /// 各列的宽度 (字符数)
+    pub widths: Vec<f64>,
+    pub validations: Vec<Validation>,
+}
+
+/// 数据有效性作用的最后一行
+const VALIDATION_ROWS: usize = 1000;
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+/// 列号 (从 0 开始) 对应的列名，如 0 → A、26 → AA
+fn column_name(mut index: usize) -> String {
+    let mut name = Vec::new();
+    loop {
+        name.push(b'A' + (index % 26) as u8);
+        if index < 26 {
+            break;
+        }
+        index = index / 26 - 1;
+    }
+    name.reverse();
+    String::from_utf8(name).unwrap_or_default()
+}
+
+fn worksheet_xml(sheet: &Worksheet) -> String {
+    let mut xml = String::from(r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?><worksheet xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main">"#);
+    xml.push_str(r#"<sheetViews><sheetView workbookViewId="0"><pane ySplit="1" topLeftCell="A2" activePane="bottomLeft" state="frozen"/></sheetView></sheetViews>"#);
+    if !sheet.widths.is_empty() {
+        xml.push_str("<cols>");
+        for (i, width) in sheet.widths.iter().enumerate() {
+            xml.push_str(&format!(r#"<col min="{0}" max="{0}" width="{1}" customWidth="1"/>"#, i + 1, width));
+        }
+        xml.push_str("</cols>");
+    }
+    xml.push_str("<sheetData>");
+    for (r, row) in sheet.rows.iter().enumerate() {
+        xml.push_str(&format!(r#"<row r="{}">"#, r + 1));
+        // 表头使用 styles.xml 中加粗的样式
+        let style = if r == 0 { r#" s="1""# } else { "" };
+        for (c, cell) in row.iter().enumerate() {
+            let reference = format!("{}{}", column_name(c), r + 1);
+            match cell {
+                Cell::Text(text) if text.is_empty() => {}
+                Cell::Text(text) => xml.push_str(&format!(r#"<c r="{}"{} t="inlineStr"><is><t xml:space="preserve">{}</t></is></c>"#, reference, style, escape(text))),
+                Cell::Number(n) => xml.push_str(&format!(r#"<c r="{}"{}><v>{}</v></c>"#, reference, style, n)),
+            }
+        }
+        xml.push_str("</row>");
+    }
+    xml.push_str("</sheetData>");
+    if !sheet.validations.is_empty() {
+        xml.push_str(&format!(r#"<dataValidations count="{}">"#, sheet.validations.len()));
+        for v in &sheet.validations {
+            let column = column_name(v.column);
+            let (kind, formula) = match &v.rule {
+                Rule::Any => (String::new(), String::new()),
+                Rule::List(items) => (r#" type="list" showErrorMessage="1""#.to_string(), format!("<formula1>\"{}\"</formula1>", escape(&items.join(",")))),
+                Rule::WholeAtLeast(min) => (r#" type="whole" operator="greaterThanOrEqual" showErrorMessage="1""#.to_string(), format!("<formula1>{}</formula1>", min)),
+            };
+            xml.push_str(&format!(
+                r#"<dataValidation{} allowBlank="1" showInputMessage="1" errorTitle="{}" error="{}" promptTitle="{}" prompt="{}" sqref="{}2:{}{}">{}</dataValidation>"#,
+                kind,
+                escape(&v.title),
+                escape(&v.prompt),
+                escape(&v.title),
+                escape(&v.prompt),
+                column,
+                column,
+                VALIDATION_ROWS,
+                formula,
+            ));
+        }
+        xml.push_str("</dataValidations>");
+    }
+    xml.push_str("</worksheet>");
+    xml
+}
+
+/// 生成 xlsx 文件，单元格以内联字符串写入，不需要共享字符串表
+pub fn write_xlsx(sheets: &[Worksheet]) -> Result<Vec<u8>, String> {
+    let mut content_types = String::from(r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?><Types xmlns="http://schemas.openxmlformats.org/package/2006/content-types"><Default Extension="rels" ContentType="application/vnd.openxmlformats-package.relationships+xml"/><Default Extension="xml" ContentType="application/xml"/><Override PartName="/xl/workbook.xml" ContentType="application/vnd.openxmlformats-officedocument.spreadsheetml.sheet.main+xml"/><Override PartName="/xl/styles.xml" ContentType="application/vnd.openxmlformats-officedocument.spreadsheetml.styles+xml"/>"#);
+    let mut workbook = String::from(r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?><workbook xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main" xmlns:r="http://schemas.openxmlformats.org/officeDocument/2006/relationships"><sheets>"#);
+    let mut rels = String::from(r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?><Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships">"#);
+    for (i, sheet) in sheets.iter().enumerate() {
+        let n = i + 1;
+        content_types.push_str(&format!(r#"<Override PartName="/xl/worksheets/sheet{}.xml" ContentType="application/vnd.openxmlformats-officedocument.spreadsheetml.worksheet+xml"/>"#, n));
+        workbook.push_str(&format!(r#"<sheet name="{}" sheetId="{}" r:id="rId{}"/>"#, escape(&sheet.name), n, n));
+        rels.push_str(&format!(r#"<Relationship Id="rId{}" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/worksheet" Target="worksheets/sheet{}.xml"/>"#, n, n));
+    }
+    content_types.push_str("</Types>");
+    workbook.push_str("</sheets></workbook>");
+    rels.push_str(&format!(r#"<Relationship Id="rId{}" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/styles" Target="styles.xml"/></Relationships>"#, sheets.len() + 1));
+
+    let mut parts = vec![
+        ("[Content_Types].xml".to_string(), content_types),
+        ("_rels/.rels".to_string(), r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?><Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships"><Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/officeDocument" Target="xl/workbook.xml"/></Relationships>"#.to_string()),
+        ("xl/workbook.xml".to_string(), workbook),
+        ("xl/_rels/workbook.xml.rels".to_string(), rels),
+        // 样式 0 为默认，样式 1 为加粗的表头
+        ("xl/styles.xml".to_string(), r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?><styleSheet xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main"><fonts count="2"><font><sz val="11"/><name val="Calibri"/></font><font><b/><sz val="11"/><name val="Calibri"/></font></fonts><fills count="2"><fill><patternFill patternType="none"/></fill><fill><patternFill patternType="gray125"/></fill></fills><borders count="1"><border><left/><right/><top/><bottom/><diagonal/></border></borders><cellStyleXfs count="1"><xf numFmtId="0" fontId="0" fillId="0" borderId="0"/></cellStyleXfs><cellXfs count="2"><xf numFmtId="0" fontId="0" fillId="0" borderId="0" xfId="0"/><xf numFmtId="0" fontId="1" fillId="0" borderId="0" xfId="0" applyFont="1"/></cellXfs></styleSheet>"#.to_string()),
+    ];
+    for (i, sheet) in sheets.iter().enumerate() {
+        parts.push((format!("xl/worksheets/sheet{}.xml", i + 1), worksheet_xml(sheet)));
+    }
+
+    let mut zip = zip::ZipWriter::new(Cursor::new(Vec::new()));
+    for (name, content) in parts {
+        zip.start_file(name, FileOptions::default()).map_err(|e| e.to_string())?;
+        zip.write_all(content.as_bytes()).map_err(|e| e.to_string())?;
+    }
+    zip.finish().map(Cursor::into_inner).map_err(|e| e.to_string())
+}
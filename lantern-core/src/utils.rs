@@ -0,0 +1,69 @@
+use chrono::{FixedOffset, NaiveDateTime, Utc};
+use std::net::UdpSocket;
+
+pub fn get_local_ip() -> Option<String> {
+    let socket = UdpSocket::bind("0.0.0.0:0").ok()?;
+    socket.connect("8.8.8.8:80").ok()?;
+    socket.local_addr().ok().map(|addr| addr.ip().to_string())
+}
+
+/// 默认的活动时区：UTC+8
+pub const DEFAULT_UTC_OFFSET_SECS: i32 = 8 * 3600;
+
+/// 显示与导出时间的格式
+pub const DATETIME_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+/// 当前 UTC 时间，数据库中的时间一律以 UTC 保存
+pub fn now_utc() -> NaiveDateTime {
+    Utc::now().naive_utc()
+}
+
+/// UTC 时间转换为活动时区的本地时间
+pub fn to_local(utc: NaiveDateTime, tz: FixedOffset) -> NaiveDateTime {
+    utc + chrono::Duration::seconds(tz.local_minus_utc() as i64)
+}
+
+/// 活动时区的本地时间转换为 UTC，用于保存管理员输入的时间
+pub fn to_utc(local: NaiveDateTime, tz: FixedOffset) -> NaiveDateTime {
+    local - chrono::Duration::seconds(tz.local_minus_utc() as i64)
+}
+
+/// 按活动时区格式化为 `YYYY-MM-DD HH:MM:SS`
+pub fn format_local(utc: NaiveDateTime, tz: FixedOffset) -> String {
+    to_local(utc, tz).format(DATETIME_FORMAT).to_string()
+}
+
+/// 解析时区配置：`+08:00`、`-05:30`、`+8`、`UTC+8`，`UTC`/`Z` 表示零时区
+pub fn parse_utc_offset(value: &str) -> Option<FixedOffset> {
+    let value = value.trim();
+    let value = value.strip_prefix("UTC").or_else(|| value.strip_prefix("GMT")).unwrap_or(value).trim();
+    if value.is_empty() || value == "Z" {
+        return FixedOffset::east_opt(0);
+    }
+    let (sign, rest) = match value.as_bytes()[0] {
+        b'+' => (1, &value[1..]),
+        b'-' => (-1, &value[1..]),
+        _ => return None,
+    };
+    let (hours, minutes) = match rest.split_once(':') {
+        Some((h, m)) => (h.parse::<i32>().ok()?, m.parse::<i32>().ok()?),
+        None => (rest.parse::<i32>().ok()?, 0),
+    };
+    if !(0..60).contains(&minutes) {
+        return None;
+    }
+    FixedOffset::east_opt(sign * (hours * 3600 + minutes * 60))
+}
+
+pub fn get_time_range_display(start: NaiveDateTime, end: NaiveDateTime) -> String {
+    if start.date() == end.date() {
+        format!("<p>{}</p><p>{}~{}</p>", 
+            start.format("%Y/%m/%d"), 
+            start.format("%H:%M"), 
+            end.format("%H:%M"))
+    } else {
+        format!("<p>{}~</p><p> {}</p>", 
+            start.format("%m/%d %H:%M"), 
+            end.format("%m/%d %H:%M"))
+    }
+}
@@ -0,0 +1,91 @@
+use axum::{
+    body::Body,
+    extract::ConnectInfo,
+    http::{header, Method, Request, StatusCode},
+    Router,
+};
+use lantern_core::{build_router, Config};
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use tower::ServiceExt;
+
+const ADMIN_ORIGIN: &str = "tauri://localhost";
+
+struct TestApp {
+    router: Router,
+    dir: PathBuf,
+}
+
+impl Drop for TestApp {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.dir);
+    }
+}
+
+/// 桌面端配置：本机访问免登录，管理窗口的来源可以跨域访问
+async fn desktop_app() -> TestApp {
+    let dir = std::env::temp_dir().join(format!("lantern-test-{}", uuid::Uuid::new_v4().simple()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let mut config = Config::new(dir.join("lantern.db"));
+    config.template_dir = dir.clone();
+    config.trust_local_admin = true;
+    config.admin_origins = vec![ADMIN_ORIGIN.to_string()];
+    TestApp { router: build_router(config).await, dir }
+}
+
+/// 从 `peer` 连接到后端的请求，`headers` 为浏览器带的 Host、Origin 等
+fn request(method: Method, uri: &str, peer: [u8; 4], headers: &[(header::HeaderName, &str)]) -> Request<Body> {
+    let mut builder = Request::builder().method(method).uri(uri);
+    for (name, value) in headers {
+        builder = builder.header(name, *value);
+    }
+    let mut req = builder.body(Body::empty()).unwrap();
+    req.extensions_mut().insert(ConnectInfo(SocketAddr::from((peer, 40000))));
+    req
+}
+
+async fn status(app: &TestApp, req: Request<Body>) -> StatusCode {
+    app.router.clone().oneshot(req).await.unwrap().status()
+}
+
+#[tokio::test]
+async fn loopback_trust_requires_a_local_host_and_a_trusted_origin() {
+    let app = desktop_app().await;
+    let local = [127, 0, 0, 1];
+    let me = |peer, headers: &[(header::HeaderName, &str)]| request(Method::GET, "/pro-api/me", peer, headers);
+
+    // 同源页面、桌面端管理窗口与不带 Origin 的本机请求免登录
+    assert_eq!(status(&app, me(local, &[(header::HOST, "127.0.0.1:9000")])).await, StatusCode::OK);
+    assert_eq!(status(&app, me(local, &[(header::HOST, "localhost:9000"), (header::ORIGIN, "http://localhost:9000")])).await, StatusCode::OK);
+    assert_eq!(status(&app, me(local, &[(header::HOST, "localhost:9000"), (header::ORIGIN, ADMIN_ORIGIN)])).await, StatusCode::OK);
+
+    // 本机浏览器中的其他网页、DNS 重绑定到本机的域名与局域网设备都需要登录
+    assert_eq!(status(&app, me(local, &[(header::HOST, "127.0.0.1:9000"), (header::ORIGIN, "https://evil.example")])).await, StatusCode::UNAUTHORIZED);
+    assert_eq!(status(&app, me(local, &[(header::HOST, "evil.example:9000"), (header::ORIGIN, "http://evil.example:9000")])).await, StatusCode::UNAUTHORIZED);
+    assert_eq!(status(&app, me([192, 168, 1, 20], &[(header::HOST, "192.168.1.2:9000")])).await, StatusCode::UNAUTHORIZED);
+
+    let delete = request(Method::DELETE, "/pro-api/user/1", local, &[(header::HOST, "127.0.0.1:9000"), (header::ORIGIN, "https://evil.example")]);
+    assert_eq!(status(&app, delete).await, StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn cors_is_limited_to_admin_origins() {
+    let app = desktop_app().await;
+    let preflight = |origin| {
+        request(Method::OPTIONS, "/pro-api/me", [127, 0, 0, 1], &[
+            (header::HOST, "localhost:9000"),
+            (header::ORIGIN, origin),
+            (header::ACCESS_CONTROL_REQUEST_METHOD, "DELETE"),
+            (header::ACCESS_CONTROL_REQUEST_HEADERS, "authorization"),
+        ])
+    };
+    let allowed = |origin| {
+        let app = &app;
+        async move {
+            let res = app.router.clone().oneshot(preflight(origin)).await.unwrap();
+            res.headers().get(header::ACCESS_CONTROL_ALLOW_ORIGIN).map(|v| v.to_str().unwrap().to_string())
+        }
+    };
+    assert_eq!(allowed(ADMIN_ORIGIN).await.as_deref(), Some(ADMIN_ORIGIN));
+    assert_eq!(allowed("https://evil.example").await, None);
+}
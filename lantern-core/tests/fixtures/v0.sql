@@ -0,0 +1,48 @@
+-- 引入版本化迁移之前的 lantern.db：由旧版 main.rs 中的 CREATE TABLE IF NOT EXISTS 直接建表
+CREATE TABLE IF NOT EXISTS users (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    username TEXT NOT NULL,
+    avatar TEXT,
+    user_code TEXT UNIQUE,
+    token TEXT UNIQUE,
+    register_time DATETIME DEFAULT CURRENT_TIMESTAMP
+);
+CREATE TABLE IF NOT EXISTS riddles (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    question TEXT NOT NULL,
+    remark TEXT,
+    options_json TEXT NOT NULL DEFAULT '[]',
+    answer TEXT NOT NULL,
+    add_time DATETIME DEFAULT CURRENT_TIMESTAMP,
+    is_solved BOOLEAN DEFAULT 0,
+    solver_id INTEGER,
+    FOREIGN KEY (solver_id) REFERENCES users(id)
+);
+CREATE TABLE IF NOT EXISTS activities (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name TEXT NOT NULL,
+    start_time DATETIME NOT NULL,
+    end_time DATETIME NOT NULL
+);
+CREATE TABLE IF NOT EXISTS guess_records (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    user_id INTEGER NOT NULL,
+    riddle_id INTEGER NOT NULL,
+    solve_time DATETIME DEFAULT CURRENT_TIMESTAMP,
+    is_solved BOOLEAN DEFAULT 0,
+    FOREIGN KEY (user_id) REFERENCES users(id),
+    FOREIGN KEY (riddle_id) REFERENCES riddles(id),
+    UNIQUE(user_id, riddle_id)
+);
+
+INSERT INTO users (id, username, avatar, user_code, token, register_time) VALUES
+    (1, '小明', '/avatar/2026/02/23/a.png', 'A1B2C3D4', 'token-1', '2026-02-23 19:00:00'),
+    (2, '小红', '', 'E5F6A7B8', 'token-2', '2026-02-23 19:05:00');
+INSERT INTO riddles (id, question, remark, options_json, answer, add_time, is_solved, solver_id) VALUES
+    (1, '一口咬掉牛尾巴', '打一字', '["告","吉","舌"]', '告', '2026-02-20 10:00:00', 1, 1),
+    (2, '千条线，万条线，掉到水里看不见', '打一自然现象', '["雨","雪","雾"]', '雨', '2026-02-20 10:00:00', 0, NULL);
+INSERT INTO activities (id, name, start_time, end_time) VALUES
+    (1, '元宵猜灯谜', '2026-02-23 18:00:00', '2026-02-23 22:00:00');
+INSERT INTO guess_records (id, user_id, riddle_id, solve_time, is_solved) VALUES
+    (1, 1, 1, '2026-02-23 19:10:00', 1),
+    (2, 2, 2, '2026-02-23 19:12:00', 0);
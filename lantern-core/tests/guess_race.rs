@@ -0,0 +1,215 @@
+use axum::{
+    body::Body,
+    http::{header, Request, StatusCode},
+    Router,
+};
+use lantern_core::{build_router, Config};
+use serde_json::{json, Value};
+use std::path::PathBuf;
+use std::sync::Arc;
+use tokio::sync::Barrier;
+use tower::ServiceExt;
+
+const PLAYERS: usize = 20;
+const ADMIN_PASSWORD: &str = "race-test-password";
+
+struct TestApp {
+    router: Router,
+    dir: PathBuf,
+}
+
+impl Drop for TestApp {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.dir);
+    }
+}
+
+async fn app() -> TestApp {
+    let dir = std::env::temp_dir().join(format!("lantern-test-{}", uuid::Uuid::new_v4().simple()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let mut config = Config::new(dir.join("lantern.db"));
+    config.template_dir = dir.clone();
+    config.initial_admin_password = Some(ADMIN_PASSWORD.to_string());
+    TestApp { router: build_router(config).await, dir }
+}
+
+async fn send(router: &Router, req: Request<Body>) -> (StatusCode, Option<String>, Value) {
+    let res = router.clone().oneshot(req).await.unwrap();
+    let status = res.status();
+    let cookie = res
+        .headers()
+        .get(header::SET_COOKIE)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.split(';').next())
+        .map(str::to_string);
+    let bytes = axum::body::to_bytes(res.into_body(), usize::MAX).await.unwrap();
+    (status, cookie, serde_json::from_slice(&bytes).unwrap_or(Value::Null))
+}
+
+fn json_request(uri: &str, token: &str, body: Value) -> Request<Body> {
+    Request::post(uri)
+        .header(header::CONTENT_TYPE, "application/json")
+        .header(header::AUTHORIZATION, format!("Bearer {}", token))
+        .body(Body::from(body.to_string()))
+        .unwrap()
+}
+
+/// 以管理员身份创建一个进行中的活动和一道灯谜，返回灯谜 ID
+async fn create_riddle(router: &Router, answer: &str) -> i64 {
+    create_limited_riddle(router, answer, json!({})).await
+}
+
+/// 同 `create_riddle`，`limits` 中的作答次数、间隔等设置写入活动
+async fn create_limited_riddle(router: &Router, answer: &str, limits: Value) -> i64 {
+    let login = Request::post("/pro-api/login")
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(Body::from(json!({ "username": "admin", "password": ADMIN_PASSWORD }).to_string()))
+        .unwrap();
+    let (_, _, body) = send(router, login).await;
+    let token = body["data"]["token"].as_str().expect("admin login failed").to_string();
+
+    let mut activity = json!({
+        "name": "并发测试",
+        "start_time": "2000-01-01 00:00:00",
+        "end_time": "2099-12-31 23:59:59"
+    });
+    activity.as_object_mut().unwrap().extend(limits.as_object().cloned().unwrap_or_default());
+    let (_, _, body) = send(router, json_request("/pro-api/activities", &token, activity)).await;
+    let activity_id = body["data"]["id"].as_i64().unwrap();
+
+    let (_, _, body) = send(router, json_request("/pro-api/riddles", &token, json!({
+        "question": "一口咬掉牛尾巴",
+        "answer": answer,
+        "activity_id": activity_id
+    }))).await;
+    body["data"]["id"].as_i64().unwrap()
+}
+
+/// 注册一个玩家，返回会话 cookie
+async fn login_player(router: &Router, username: &str) -> String {
+    let boundary = "lantern-test-boundary";
+    let body = format!(
+        "--{b}\r\nContent-Disposition: form-data; name=\"username\"\r\n\r\n{u}\r\n--{b}--\r\n",
+        b = boundary,
+        u = username
+    );
+    let req = Request::post("/login")
+        .header(header::CONTENT_TYPE, format!("multipart/form-data; boundary={}", boundary))
+        .body(Body::from(body))
+        .unwrap();
+    let (status, cookie, _) = send(router, req).await;
+    assert_eq!(status, StatusCode::OK);
+    cookie.expect("login did not set a session cookie")
+}
+
+fn guess_request(cookie: &str, riddle_id: i64, answer: &str) -> Request<Body> {
+    Request::post("/guess")
+        .header(header::CONTENT_TYPE, "application/json")
+        .header(header::COOKIE, cookie)
+        .body(Body::from(json!({ "riddle_id": riddle_id, "answer": answer }).to_string()))
+        .unwrap()
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 8)]
+async fn parallel_correct_guesses_have_exactly_one_winner() {
+    let app = app().await;
+    let riddle_id = create_riddle(&app.router, "告").await;
+
+    let mut cookies = Vec::new();
+    for i in 0..PLAYERS {
+        cookies.push(login_player(&app.router, &format!("玩家{}", i)).await);
+    }
+
+    // 所有请求就绪后同时发出
+    let barrier = Arc::new(Barrier::new(PLAYERS));
+    let tasks: Vec<_> = cookies
+        .into_iter()
+        .map(|cookie| {
+            let router = app.router.clone();
+            let barrier = barrier.clone();
+            tokio::spawn(async move {
+                barrier.wait().await;
+                send(&router, guess_request(&cookie, riddle_id, "告")).await
+            })
+        })
+        .collect();
+
+    let mut winners = 0;
+    for task in tasks {
+        let (status, _, body) = task.await.unwrap();
+        assert_eq!(status, StatusCode::OK, "unexpected response: {}", body);
+        if body["success"] == json!(true) {
+            winners += 1;
+        } else {
+            let msg = body["msg"].as_str().unwrap_or_default();
+            assert!(msg.contains("手慢了") || msg.contains("抢先猜中"), "unexpected loser message: {}", msg);
+        }
+    }
+    assert_eq!(winners, 1);
+
+    // 再猜一次只会得到失败结果，获胜者不会被覆盖
+    let late = login_player(&app.router, "迟到的玩家").await;
+    let (_, _, body) = send(&app.router, guess_request(&late, riddle_id, "告")).await;
+    assert_eq!(body["success"], json!(false));
+}
+
+#[tokio::test]
+async fn sequential_guess_after_solve_is_rejected() {
+    let app = app().await;
+    let riddle_id = create_riddle(&app.router, "雨").await;
+
+    let first = login_player(&app.router, "甲").await;
+    let second = login_player(&app.router, "乙").await;
+
+    // 公开接口在灯谜被猜中前不透露谜底
+    let public = || Request::get(format!("/frontend/riddle/{}", riddle_id)).body(Body::empty()).unwrap();
+    let (_, _, body) = send(&app.router, public()).await;
+    assert_eq!(body["data"]["answer"], Value::Null, "{}", body);
+    assert!(body["data"].get("options_json").is_none(), "{}", body);
+
+    let (_, _, body) = send(&app.router, guess_request(&first, riddle_id, "雨")).await;
+    assert_eq!(body["success"], json!(true));
+    let (_, _, body) = send(&app.router, public()).await;
+    assert_eq!(body["data"]["answer"], "雨", "{}", body);
+
+    let (_, _, body) = send(&app.router, guess_request(&second, riddle_id, "雨")).await;
+    assert_eq!(body["success"], json!(false));
+    assert!(body["msg"].as_str().unwrap_or_default().contains("甲"));
+}
+
+/// 同一玩家同时提交多次答案，返回被接受 (判定过答案) 的次数
+async fn parallel_wrong_guesses(router: &Router, cookie: &str, riddle_id: i64) -> usize {
+    let barrier = Arc::new(Barrier::new(PLAYERS));
+    let tasks: Vec<_> = (0..PLAYERS)
+        .map(|_| {
+            let router = router.clone();
+            let barrier = barrier.clone();
+            let cookie = cookie.to_string();
+            tokio::spawn(async move {
+                barrier.wait().await;
+                send(&router, guess_request(&cookie, riddle_id, "错")).await
+            })
+        })
+        .collect();
+
+    let mut judged = 0;
+    for task in tasks {
+        let (status, _, body) = task.await.unwrap();
+        assert_eq!(status, StatusCode::OK, "unexpected response: {}", body);
+        if body["msg"].as_str().unwrap_or_default().starts_with("答案不对") {
+            judged += 1;
+        }
+    }
+    judged
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 8)]
+async fn parallel_guesses_respect_attempt_limit_and_cooldown() {
+    let app = app().await;
+    let limited = create_limited_riddle(&app.router, "告", json!({ "max_attempts": 3 })).await;
+    let cooled = create_limited_riddle(&app.router, "雨", json!({ "cooldown_secs": 60 })).await;
+    let cookie = login_player(&app.router, "连点的玩家").await;
+
+    assert_eq!(parallel_wrong_guesses(&app.router, &cookie, limited).await, 3);
+    assert_eq!(parallel_wrong_guesses(&app.router, &cookie, cooled).await, 1);
+}
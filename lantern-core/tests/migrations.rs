@@ -0,0 +1,131 @@
+use chrono::FixedOffset;
+use lantern_core::db;
+use sqlx::sqlite::{SqlitePool, SqlitePoolOptions};
+
+const V0_FIXTURE: &str = include_str!("fixtures/v0.sql");
+
+/// 旧数据默认的时区 (UTC+8)
+fn beijing() -> FixedOffset {
+    FixedOffset::east_opt(8 * 3600).unwrap()
+}
+
+/// 单连接的内存数据库，连接在测试期间不会被回收
+async fn memory_pool() -> SqlitePool {
+    SqlitePoolOptions::new()
+        .max_connections(1)
+        .idle_timeout(None)
+        .max_lifetime(None)
+        .connect("sqlite::memory:")
+        .await
+        .unwrap()
+}
+
+async fn columns(pool: &SqlitePool, table: &str) -> Vec<String> {
+    sqlx::query_as::<_, (String,)>(&format!("SELECT name FROM pragma_table_info('{}')", table))
+        .fetch_all(pool)
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|(name,)| name)
+        .collect()
+}
+
+#[tokio::test]
+async fn upgrades_v0_database_without_data_loss() {
+    let pool = memory_pool().await;
+    sqlx::query(V0_FIXTURE).execute(&pool).await.unwrap();
+    assert_eq!(db::current_version(&pool).await.unwrap(), 0);
+
+    let version = db::migrate(&pool, beijing()).await.unwrap();
+    assert_eq!(version, db::latest_version());
+    assert_eq!(db::current_version(&pool).await.unwrap(), db::latest_version());
+
+    let users: Vec<(i64, String, Option<String>)> = sqlx::query_as("SELECT id, username, token FROM users ORDER BY id")
+        .fetch_all(&pool)
+        .await
+        .unwrap();
+    assert_eq!(users, vec![
+        (1, "小明".to_string(), Some("token-1".to_string())),
+        (2, "小红".to_string(), Some("token-2".to_string())),
+    ]);
+
+    let riddles: Vec<(i64, String, bool, Option<i64>)> = sqlx::query_as("SELECT id, answer, is_solved, solver_id FROM riddles ORDER BY id")
+        .fetch_all(&pool)
+        .await
+        .unwrap();
+    assert_eq!(riddles, vec![(1, "告".to_string(), true, Some(1)), (2, "雨".to_string(), false, None)]);
+
+    let (activity, start_time): (String, String) = sqlx::query_as("SELECT name, start_time FROM activities WHERE id = 1").fetch_one(&pool).await.unwrap();
+    assert_eq!(activity, "元宵猜灯谜");
+
+    // v11：原来按 UTC+8 保存的时间转换为 UTC
+    assert_eq!(start_time, "2026-02-23 10:00:00");
+    let (solve_time,): (String,) = sqlx::query_as("SELECT solve_time FROM guess_records WHERE id = 1").fetch_one(&pool).await.unwrap();
+    assert_eq!(solve_time, "2026-02-23 11:10:00");
+
+    // v4：已有灯谜归入原来的活动
+    let (orphans,): (i64,) = sqlx::query_as("SELECT COUNT(*) FROM riddles WHERE activity_id IS NOT 1").fetch_one(&pool).await.unwrap();
+    assert_eq!(orphans, 0);
+
+    let (records,): (i64,) = sqlx::query_as("SELECT COUNT(*) FROM guess_records").fetch_one(&pool).await.unwrap();
+    assert_eq!(records, 2);
+
+    // v7：去掉 UNIQUE(user_id, riddle_id)，同一玩家可以多次作答
+    sqlx::query("INSERT INTO guess_records (user_id, riddle_id, is_solved, answer) VALUES (2, 2, 0, '雪')")
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    assert!(columns(&pool, "users").await.contains(&"token_expires".to_string()));
+    assert!(!columns(&pool, "admins").await.is_empty());
+}
+
+#[tokio::test]
+async fn fresh_database_reaches_latest_version() {
+    let pool = memory_pool().await;
+    assert_eq!(db::migrate(&pool, beijing()).await.unwrap(), db::latest_version());
+
+    let (applied,): (i64,) = sqlx::query_as("SELECT COUNT(*) FROM schema_version").fetch_one(&pool).await.unwrap();
+    assert_eq!(applied, db::MIGRATIONS.len() as i64);
+}
+
+#[tokio::test]
+async fn migrate_is_idempotent() {
+    let pool = memory_pool().await;
+    sqlx::query(V0_FIXTURE).execute(&pool).await.unwrap();
+
+    db::migrate(&pool, beijing()).await.unwrap();
+    db::migrate(&pool, beijing()).await.unwrap();
+
+    let (applied,): (i64,) = sqlx::query_as("SELECT COUNT(*) FROM schema_version").fetch_one(&pool).await.unwrap();
+    assert_eq!(applied, db::MIGRATIONS.len() as i64);
+}
+
+#[test]
+fn migration_versions_are_strictly_increasing() {
+    let versions: Vec<i64> = db::MIGRATIONS.iter().map(|m| m.version).collect();
+    assert!(versions.windows(2).all(|w| w[0] < w[1]), "versions: {:?}", versions);
+}
+
+#[tokio::test]
+async fn legacy_local_times_are_shifted_by_the_configured_timezone() {
+    let pool = memory_pool().await;
+    sqlx::query(V0_FIXTURE).execute(&pool).await.unwrap();
+    // 带玩家会话过期时间的旧库 (v3 的列已存在时跳过)
+    sqlx::query("ALTER TABLE users ADD COLUMN token_expires DATETIME").execute(&pool).await.unwrap();
+    sqlx::query("UPDATE users SET token_expires = '2026-03-01 19:00:00' WHERE id = 1").execute(&pool).await.unwrap();
+
+    // 部署在 UTC-5 的旧数据，本地时间加 5 小时为 UTC
+    db::migrate(&pool, FixedOffset::west_opt(5 * 3600).unwrap()).await.unwrap();
+
+    let (start_time, end_time, paused_at): (String, String, Option<String>) = sqlx::query_as("SELECT start_time, end_time, paused_at FROM activities WHERE id = 1").fetch_one(&pool).await.unwrap();
+    assert_eq!((start_time.as_str(), end_time.as_str(), paused_at), ("2026-02-23 23:00:00", "2026-02-24 03:00:00", None));
+    let (register_time, token_expires): (String, Option<String>) = sqlx::query_as("SELECT register_time, token_expires FROM users WHERE id = 1").fetch_one(&pool).await.unwrap();
+    assert_eq!((register_time.as_str(), token_expires.as_deref()), ("2026-02-24 00:00:00", Some("2026-03-02 00:00:00")));
+    let (token_expires,): (Option<String>,) = sqlx::query_as("SELECT token_expires FROM users WHERE id = 2").fetch_one(&pool).await.unwrap();
+    assert_eq!(token_expires, None);
+    let (add_time,): (String,) = sqlx::query_as("SELECT add_time FROM riddles WHERE id = 1").fetch_one(&pool).await.unwrap();
+    assert_eq!(add_time, "2026-02-20 15:00:00");
+    let (solve_time,): (String,) = sqlx::query_as("SELECT solve_time FROM guess_records WHERE id = 1").fetch_one(&pool).await.unwrap();
+    assert_eq!(solve_time, "2026-02-24 00:10:00");
+}
@@ -0,0 +1,380 @@
+use axum::{
+    body::Body,
+    http::{header, Request},
+    Router,
+};
+use lantern_core::{build_router, Config};
+use calamine::Reader;
+use serde_json::{json, Value};
+use std::io::{Cursor, Write};
+use std::path::PathBuf;
+use tower::ServiceExt;
+
+const ADMIN_PASSWORD: &str = "import-test-password";
+const BOUNDARY: &str = "lantern-test-boundary";
+
+struct TestApp {
+    router: Router,
+    dir: PathBuf,
+    token: String,
+}
+
+impl Drop for TestApp {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.dir);
+    }
+}
+
+async fn send(router: &Router, req: Request<Body>) -> Value {
+    let res = router.clone().oneshot(req).await.unwrap();
+    let bytes = axum::body::to_bytes(res.into_body(), usize::MAX).await.unwrap();
+    serde_json::from_slice(&bytes).unwrap_or(Value::Null)
+}
+
+/// 启动应用并以管理员登录，创建一个进行中的活动
+async fn app() -> TestApp {
+    let dir = std::env::temp_dir().join(format!("lantern-test-{}", uuid::Uuid::new_v4().simple()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let mut config = Config::new(dir.join("lantern.db"));
+    config.template_dir = dir.clone();
+    config.initial_admin_password = Some(ADMIN_PASSWORD.to_string());
+    let router = build_router(config).await;
+
+    let login = Request::post("/pro-api/login")
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(Body::from(json!({ "username": "admin", "password": ADMIN_PASSWORD }).to_string()))
+        .unwrap();
+    let token = send(&router, login).await["data"]["token"].as_str().expect("admin login failed").to_string();
+    let activity = Request::get("/pro-api/activity").header(header::AUTHORIZATION, format!("Bearer {}", token)).body(Body::empty()).unwrap();
+    send(&router, activity).await;
+    TestApp { router, dir, token }
+}
+
+/// 生成只有一个工作表的 xlsx，单元格都按文本写入
+fn xlsx(rows: &[&[&str]]) -> Vec<u8> {
+    let mut sheet = String::from(r#"<?xml version="1.0" encoding="UTF-8"?><worksheet xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main"><sheetData>"#);
+    for row in rows {
+        sheet.push_str("<row>");
+        for cell in row.iter() {
+            sheet.push_str(&format!(r#"<c t="inlineStr"><is><t>{}</t></is></c>"#, cell));
+        }
+        sheet.push_str("</row>");
+    }
+    sheet.push_str("</sheetData></worksheet>");
+
+    let parts = [
+        ("[Content_Types].xml", r#"<?xml version="1.0" encoding="UTF-8"?><Types xmlns="http://schemas.openxmlformats.org/package/2006/content-types"><Default Extension="rels" ContentType="application/vnd.openxmlformats-package.relationships+xml"/><Default Extension="xml" ContentType="application/xml"/><Override PartName="/xl/workbook.xml" ContentType="application/vnd.openxmlformats-officedocument.spreadsheetml.sheet.main+xml"/><Override PartName="/xl/worksheets/sheet1.xml" ContentType="application/vnd.openxmlformats-officedocument.spreadsheetml.worksheet+xml"/></Types>"#.to_string()),
+        ("_rels/.rels", r#"<?xml version="1.0" encoding="UTF-8"?><Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships"><Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/officeDocument" Target="xl/workbook.xml"/></Relationships>"#.to_string()),
+        ("xl/workbook.xml", r#"<?xml version="1.0" encoding="UTF-8"?><workbook xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main" xmlns:r="http://schemas.openxmlformats.org/officeDocument/2006/relationships"><sheets><sheet name="Sheet1" sheetId="1" r:id="rId1"/></sheets></workbook>"#.to_string()),
+        ("xl/_rels/workbook.xml.rels", r#"<?xml version="1.0" encoding="UTF-8"?><Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships"><Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/worksheet" Target="worksheets/sheet1.xml"/></Relationships>"#.to_string()),
+        ("xl/worksheets/sheet1.xml", sheet),
+    ];
+    let mut zip = zip::ZipWriter::new(Cursor::new(Vec::new()));
+    for (name, content) in parts {
+        zip.start_file(name, zip::write::FileOptions::default()).unwrap();
+        zip.write_all(content.as_bytes()).unwrap();
+    }
+    zip.finish().unwrap().into_inner()
+}
+
+async fn import(app: &TestApp, file: Vec<u8>, fields: &[(&str, &str)]) -> Value {
+    import_file(app, "riddles.xlsx", file, fields).await
+}
+
+async fn import_file(app: &TestApp, filename: &str, file: Vec<u8>, fields: &[(&str, &str)]) -> Value {
+    upload(app, "/pro-api/riddles/import", filename, file, fields).await
+}
+
+async fn upload(app: &TestApp, uri: &str, filename: &str, file: Vec<u8>, fields: &[(&str, &str)]) -> Value {
+    let mut body = Vec::new();
+    for (name, value) in fields {
+        body.extend(format!("--{}\r\nContent-Disposition: form-data; name=\"{}\"\r\n\r\n{}\r\n", BOUNDARY, name, value).into_bytes());
+    }
+    body.extend(format!("--{}\r\nContent-Disposition: form-data; name=\"file\"; filename=\"{}\"\r\n\r\n", BOUNDARY, filename).into_bytes());
+    body.extend(file);
+    body.extend(format!("\r\n--{}--\r\n", BOUNDARY).into_bytes());
+    let req = Request::post(uri)
+        .header(header::CONTENT_TYPE, format!("multipart/form-data; boundary={}", BOUNDARY))
+        .header(header::AUTHORIZATION, format!("Bearer {}", app.token))
+        .body(Body::from(body))
+        .unwrap();
+    send(&app.router, req).await
+}
+
+async fn riddles(app: &TestApp) -> Vec<Value> {
+    let req = Request::get("/pro-api/riddles?page=1&pageSize=100&sort=id&order=asc")
+        .header(header::AUTHORIZATION, format!("Bearer {}", app.token))
+        .body(Body::empty())
+        .unwrap();
+    send(&app.router, req).await["data"]["list"].as_array().cloned().unwrap_or_default()
+}
+
+#[tokio::test]
+async fn rows_with_errors_abort_the_whole_import() {
+    let app = app().await;
+    let file = xlsx(&[
+        &["灯谜题目", "正确答案", "选项1", "选项2", "分值"],
+        &["一口咬掉牛尾巴", "告", "告", "吉", "10"],
+        &["山上还有山", "出", "入", "人", ""],
+        &["", "", "", "", ""],
+        &["山上还有山", "出", "", "", "十"],
+    ]);
+
+    let report = import(&app, file.clone(), &[("dry_run", "1")]).await;
+    let rows = report["data"]["rows"].as_array().unwrap();
+    assert_eq!(rows.len(), 3, "blank rows are not reported: {}", report);
+    assert_eq!(rows[0]["status"], "insert");
+    assert_eq!(rows[1]["warnings"], json!(["正确答案不在选项中，已加入选项"]));
+    assert_eq!(rows[2]["row"], 5);
+    assert_eq!(rows[2]["status"], "error");
+    assert_eq!(rows[2]["errors"], json!(["分值不是数字: 十", "与第 3 行重复"]));
+
+    let report = import(&app, file, &[]).await;
+    assert_eq!(report["data"]["applied"], false);
+    assert!(riddles(&app).await.is_empty(), "nothing is written when any row fails");
+}
+
+#[tokio::test]
+async fn duplicates_are_skipped_or_updated_by_key() {
+    let app = app().await;
+    let file = xlsx(&[
+        &["编号", "灯谜题目", "正确答案", "选项1", "选项2", "分值"],
+        &["A1", "一口咬掉牛尾巴", "告", "告", "吉", "10"],
+        &["A2", "山上还有山", "出", "出", "入", "10"],
+    ]);
+    let report = import(&app, file.clone(), &[]).await;
+    assert_eq!(report["data"]["inserted"], 2, "{}", report);
+
+    // 谜面相同 (忽略标点) 的灯谜默认跳过
+    let report = import(&app, file, &[]).await;
+    assert_eq!((report["data"]["inserted"].as_u64(), report["data"]["skipped"].as_u64()), (Some(0), Some(2)));
+
+    // 按编号更新，表格中没有的列保持不变
+    let file = xlsx(&[
+        &["编号", "灯谜题目", "正确答案", "分值"],
+        &["A1", "一口咬掉牛尾巴！", "告", "30"],
+        &["A3", "画时圆，写时方", "日", "5"],
+    ]);
+    let report = import(&app, file, &[("mode", "upsert"), ("key", "external_id")]).await;
+    assert_eq!((report["data"]["inserted"].as_u64(), report["data"]["updated"].as_u64()), (Some(1), Some(1)), "{}", report);
+
+    let list = riddles(&app).await;
+    assert_eq!(list.len(), 3);
+    let updated = list.iter().find(|r| r["external_id"] == "A1").unwrap();
+    assert_eq!(updated["question"], "一口咬掉牛尾巴！");
+    assert_eq!(updated["points"], 30);
+    assert_eq!(updated["options"].as_array().map(Vec::len), Some(2));
+
+    // 没有选项列时改了谜底，已有的选项中也要有新谜底，预览与导入结果一致
+    let file = xlsx(&[
+        &["编号", "灯谜题目", "正确答案"],
+        &["A2", "山上还有山", "岳"],
+    ]);
+    let report = import(&app, file.clone(), &[("mode", "upsert"), ("key", "external_id"), ("dry_run", "1")]).await;
+    assert_eq!(report["data"]["rows"][0]["status"], "update", "{}", report);
+    assert_eq!(report["data"]["rows"][0]["warnings"], json!(["正确答案不在选项中，已加入选项"]));
+    let report = import(&app, file, &[("mode", "upsert"), ("key", "external_id")]).await;
+    assert_eq!(report["data"]["updated"], 1, "{}", report);
+
+    let list = riddles(&app).await;
+    let changed = list.iter().find(|r| r["external_id"] == "A2").unwrap();
+    assert_eq!(changed["answer"], "岳");
+    assert_eq!(changed["options"], json!(["出", "入", "岳"]));
+}
+
+#[tokio::test]
+async fn options_keep_authored_order_and_include_the_answer() {
+    let app = app().await;
+    let file = xlsx(&[
+        &["灯谜题目", "正确答案", "选项1", "选项2", "选项3"],
+        &["一口咬掉牛尾巴", "告", "甲", "乙", "丙"],
+        &["山上还有山", "出", "甲", "乙", "丙"],
+        &["画时圆，写时方", "日", "甲", "乙", "丙"],
+        &["一加一", "王", "甲", "乙", "丙"],
+    ]);
+    let report = import(&app, file.clone(), &[("option_order", "keep"), ("seed", "7")]).await;
+    assert_eq!(report["data"]["applied"], true, "{}", report);
+    assert_eq!(report["data"]["rows"][0]["warnings"], json!(["正确答案不在选项中，已加入选项"]));
+
+    // 其余选项保持录入顺序，答案按种子插入，不总是最后一项
+    let list = riddles(&app).await;
+    let mut slots = Vec::new();
+    for riddle in &list {
+        assert_eq!(riddle["option_order"], "keep");
+        let mut options: Vec<String> = serde_json::from_value(riddle["options"].clone()).unwrap();
+        let at = options.iter().position(|o| *o == riddle["answer"]).expect("answer is among the options");
+        options.remove(at);
+        assert_eq!(options, ["甲", "乙", "丙"]);
+        slots.push(at);
+    }
+    assert!(slots.iter().any(|&at| at < 3), "answer is always last: {:?}", slots);
+
+    // 同一种子再次导入得到相同的位置
+    let report = import(&app, file, &[("option_order", "keep"), ("seed", "7"), ("mode", "append")]).await;
+    assert_eq!(report["data"]["applied"], true, "{}", report);
+    let again = riddles(&app).await;
+    for (first, second) in list.iter().zip(&again[list.len()..]) {
+        assert_eq!(first["options"], second["options"]);
+    }
+
+    let save = Request::post("/pro-api/riddles")
+        .header(header::CONTENT_TYPE, "application/json")
+        .header(header::AUTHORIZATION, format!("Bearer {}", app.token))
+        .body(Body::from(json!({ "question": "一口咬掉牛尾巴", "answer": "告", "options": ["甲", "乙"], "option_order": "keep" }).to_string()))
+        .unwrap();
+    let res = send(&app.router, save).await;
+    assert_eq!(res["code"], 200, "{}", res);
+    let options: Vec<String> = serde_json::from_str(res["data"]["options_json"].as_str().unwrap()).unwrap();
+    assert_eq!(options.len(), 3);
+    assert!(options.contains(&"告".to_string()));
+}
+
+#[tokio::test]
+async fn riddles_in_unknown_activities_are_rejected() {
+    let app = app().await;
+    let save = |body: Value| {
+        Request::post("/pro-api/riddles")
+            .header(header::CONTENT_TYPE, "application/json")
+            .header(header::AUTHORIZATION, format!("Bearer {}", app.token))
+            .body(Body::from(body.to_string()))
+            .unwrap()
+    };
+    let res = send(&app.router, save(json!({ "question": "山上还有山", "answer": "出", "activity_id": 9999 }))).await;
+    assert_eq!(res, json!({ "code": 400, "message": "活动不存在" }));
+
+    let res = send(&app.router, save(json!({ "question": "山上还有山", "answer": "出" }))).await;
+    assert_eq!(res["code"], 200, "{}", res);
+    let res = send(&app.router, save(json!({ "id": res["data"]["id"], "activity_id": 9999 }))).await;
+    assert_eq!(res, json!({ "code": 400, "message": "活动不存在" }));
+    assert_eq!(riddles(&app).await.len(), 1);
+}
+
+#[tokio::test]
+async fn csv_and_json_share_the_column_mapping() {
+    let app = app().await;
+    // WPS 默认导出 GBK 编码的 CSV，引号内可以有逗号
+    let csv = "灯谜题目,正确答案,选项1,选项2,分类\r\n\"一口咬掉牛尾巴，打一字\",告,告,吉,字谜\r\n";
+    let (gbk, _, _) = encoding_rs::GBK.encode(csv);
+    let report = import_file(&app, "riddles.csv", gbk.into_owned(), &[("option_order", "keep")]).await;
+    assert_eq!(report["data"]["inserted"], 1, "{}", report);
+
+    let json = json!([
+        { "question": "山上还有山", "answer": "出", "options_json": "[\"出\",\"入\"]", "tags": ["字谜", "山"], "points": 20 },
+        { "question": "画时圆，写时方", "answer": "日", "hints": [{ "text": "天上有一个" }] }
+    ]);
+    let report = import_file(&app, "export.json", json.to_string().into_bytes(), &[("option_order", "keep")]).await;
+    assert_eq!(report["data"]["inserted"], 2, "{}", report);
+
+    let list = riddles(&app).await;
+    assert_eq!(list[0]["question"], "一口咬掉牛尾巴，打一字");
+    assert_eq!(list[0]["category"], "字谜");
+    assert_eq!(list[1]["options"], json!(["出", "入"]));
+    assert_eq!(list[1]["tags"], json!(["字谜", "山"]));
+    assert_eq!(list[1]["points"], 20);
+    assert_eq!(list[2]["hints"][0]["text"], "天上有一个");
+}
+
+#[tokio::test]
+async fn column_mappings_are_suggested_saved_and_reused() {
+    let app = app().await;
+    // 常见的别名表头直接识别，不认识的表头忽略
+    let file = xlsx(&[&["谜面", "谜底", "选项A", "选项B", "出处"], &["一口咬掉牛尾巴", "告", "告", "吉", "谜书"]]);
+    let suggestion = upload(&app, "/pro-api/riddles/import/suggest", "bank.xlsx", file, &[]).await;
+    assert_eq!(
+        suggestion["data"]["mapping"],
+        json!({ "谜面": "question", "谜底": "answer", "选项A": "options", "选项B": "options", "出处": null }),
+        "{}",
+        suggestion
+    );
+    assert_eq!(suggestion["data"]["mapping_name"], Value::Null);
+
+    // 表头无法识别时需要列映射
+    let file = xlsx(&[&["Q", "A", "Src"], &["山上还有山", "出", "谜书"]]);
+    let report = import(&app, file.clone(), &[]).await;
+    assert_eq!(report["code"], 400, "{}", report);
+    let mapping = json!({ "Q": "question", "A": "answer", "Src": "remark" }).to_string();
+    let report = import(&app, file.clone(), &[("mapping", &mapping), ("dry_run", "1")]).await;
+    assert_eq!(report["data"]["inserted"], 1, "{}", report);
+
+    let save = Request::post("/pro-api/riddles/mappings")
+        .header(header::CONTENT_TYPE, "application/json")
+        .header(header::AUTHORIZATION, format!("Bearer {}", app.token))
+        .body(Body::from(json!({ "name": "谜书", "mapping": { "Q": "question", "A": "answer", "Src": "remark" } }).to_string()))
+        .unwrap();
+    assert_eq!(send(&app.router, save).await["code"], 200);
+
+    // 保存的映射按表头自动匹配，导入时按名称使用
+    let suggestion = upload(&app, "/pro-api/riddles/import/suggest", "bank.xlsx", file.clone(), &[]).await;
+    assert_eq!(suggestion["data"]["mapping_name"], "谜书");
+    assert_eq!(suggestion["data"]["mapping"]["Src"], "remark");
+    let report = import(&app, file, &[("mapping_name", "谜书")]).await;
+    assert_eq!(report["data"]["applied"], true, "{}", report);
+
+    let list = riddles(&app).await;
+    assert_eq!(list[0]["question"], "山上还有山");
+    assert_eq!(list[0]["remark"], "谜书");
+}
+
+#[tokio::test]
+async fn generated_template_imports_as_is() {
+    let app = app().await;
+    let req = Request::get("/pro-api/riddles/template").header(header::AUTHORIZATION, format!("Bearer {}", app.token)).body(Body::empty()).unwrap();
+    let res = app.router.clone().oneshot(req).await.unwrap();
+    assert_eq!(res.headers()[header::CONTENT_TYPE], "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet");
+    let template = axum::body::to_bytes(res.into_body(), usize::MAX).await.unwrap().to_vec();
+
+    // 填写说明与当前补上正确答案的规则一致
+    let mut workbook: calamine::Xlsx<_> = calamine::open_workbook_from_rs(Cursor::new(template.clone())).unwrap();
+    let notes = workbook.worksheet_range("填写说明").unwrap();
+    let answer_note = notes.rows().find(|r| r[0] == "正确答案").map(|r| r[2].to_string());
+    assert_eq!(answer_note, Some(format!("正确答案；{}", lantern_core::options::MISSING_ANSWER_RULE)));
+
+    // 模板的每个表头都能按名称识别，示例灯谜可以直接导入
+    let suggestion = upload(&app, "/pro-api/riddles/import/suggest", "template.xlsx", template.clone(), &[]).await;
+    let mapping = suggestion["data"]["mapping"].as_object().unwrap();
+    assert!(mapping.values().all(|c| !c.is_null()), "{}", suggestion);
+    let report = import(&app, template, &[("option_order", "keep")]).await;
+    assert_eq!((report["data"]["inserted"].as_u64(), report["data"]["failed"].as_u64()), (Some(2), Some(0)), "{}", report);
+    assert!(report["data"]["rows"].as_array().unwrap().iter().all(|r| r["warnings"] == json!([])), "{}", report);
+
+    let list = riddles(&app).await;
+    assert_eq!(list[0]["external_id"], "A001");
+    assert_eq!(list[0]["options"], json!(["告", "吉", "舌", "言"]));
+    assert_eq!(list[0]["tags"], json!(["入门", "动物"]));
+    assert_eq!(list[0]["hints"].as_array().map(Vec::len), Some(2));
+    assert_eq!((list[1]["points"].as_i64(), list[1]["difficulty"].as_i64()), (Some(20), Some(2)));
+}
+
+#[tokio::test]
+async fn riddle_fields_sharing_a_prefix_are_not_options() {
+    let app = app().await;
+    // 与灯谜列表返回的字段相同，`option_order`、`option_seed` 不能当作选项
+    let json = json!([
+        { "question": "山上还有山", "answer": "出", "options": ["出", "入"], "option_order": "keep", "option_seed": 123, "aliases_json": "[]" }
+    ]);
+    let report = import_file(&app, "export.json", json.to_string().into_bytes(), &[("option_order", "keep")]).await;
+    assert_eq!(report["data"]["inserted"], 1, "{}", report);
+    assert_eq!(report["data"]["rows"][0]["warnings"], json!([]), "{}", report);
+
+    let list = riddles(&app).await;
+    assert_eq!(list[0]["options"], json!(["出", "入"]));
+}
+
+#[tokio::test]
+async fn oversized_sheet_in_zip_is_rejected() {
+    let app = app().await;
+    // 高度压缩的表格：压缩包很小，解压后超过上传大小上限
+    let mut zip = zip::ZipWriter::new(Cursor::new(Vec::new()));
+    zip.start_file("riddles.csv", zip::write::FileOptions::default()).unwrap();
+    zip.write_all("灯谜题目,正确答案\n".as_bytes()).unwrap();
+    let padding = vec![b' '; 1024 * 1024];
+    for _ in 0..21 {
+        zip.write_all(&padding).unwrap();
+    }
+    let bundle = zip.finish().unwrap().into_inner();
+    assert!(bundle.len() < 1024 * 1024);
+
+    let report = import_file(&app, "bundle.zip", bundle, &[("dry_run", "1")]).await;
+    assert_eq!(report["code"], 400, "{}", report);
+    assert_eq!(report["message"], "表格文件超过 20 MB: riddles.csv");
+}
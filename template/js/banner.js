@@ -0,0 +1,36 @@
+// 玩家手机接收管理后台发送的横幅消息与新轮次开放的通知
+// 用法：connectPlayer(活动ID)，活动ID 为空时加入当前活动
+function connectPlayer(activityId) {
+    if (typeof io === 'undefined') {
+        return null;
+    }
+    var auth = {role: 'player'};
+    if (activityId) {
+        auth.activity_id = activityId;
+    }
+    var socket = io({auth: auth});
+    var timer = null;
+
+    function showBanner(message, durationSecs) {
+        var $banner = $('#player-banner');
+        if (!$banner.length) {
+            $banner = $('<div id="player-banner" class="player-banner"></div>').appendTo('body');
+            $banner.on('click', function () {
+                $banner.removeClass('show');
+            });
+        }
+        $banner.text(message).addClass('show');
+        clearTimeout(timer);
+        timer = setTimeout(function () {
+            $banner.removeClass('show');
+        }, (durationSecs || 10) * 1000);
+    }
+
+    socket.on('banner', function (data) {
+        showBanner(data.message, data.duration_secs);
+    });
+    socket.on('round_opened', function (data) {
+        showBanner('「' + data.name + '」开始了，新开放 ' + data.riddle_ids.length + ' 道灯谜');
+    });
+    return socket;
+}